@@ -0,0 +1,216 @@
+//! Cellular automata: a local rule applied to every cell of a [`Grid`] at once.
+
+use std::{collections::HashMap, hash::Hash};
+
+use crate::grid::{Direction, Grid};
+
+/// Where the generations of an [`Automaton`] start repeating.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    /// First generation that is seen again.
+    pub start: usize,
+    /// Generations between repeats, 1 for a fixed point.
+    pub length: usize,
+}
+
+impl Cycle {
+    /// The generation before the end of the first repeat that matches `generation`.
+    pub fn equivalent(&self, generation: usize) -> usize {
+        if generation < self.start {
+            generation
+        } else {
+            self.start + (generation - self.start) % self.length
+        }
+    }
+}
+
+/// Apply `rule` to every cell of `current`, writing the results into `next`.
+fn apply<T, R>(
+    current: &Grid<T>,
+    next: &mut Grid<T>,
+    neighbourhood: &[Direction],
+    rule: &mut R,
+) -> bool
+where
+    T: PartialEq,
+    R: FnMut(&T, &[&T]) -> T,
+{
+    let mut neighbours = Vec::with_capacity(neighbourhood.len());
+    let mut changed = false;
+    for (pos, cell) in current.iter() {
+        neighbours.clear();
+        neighbours.extend(
+            current
+                .neighbours(pos, neighbourhood.iter().copied())
+                .map(|(_, n)| n),
+        );
+        let value = rule(cell, &neighbours);
+        changed |= value != *cell;
+        next[pos] = value;
+    }
+    changed
+}
+
+/// A grid evolving under a rule, double-buffered so each generation reuses the
+/// allocation of the one before last.
+pub struct Automaton<T, R> {
+    current: Grid<T>,
+    next: Grid<T>,
+    neighbourhood: Vec<Direction>,
+    rule: R,
+    generation: usize,
+}
+
+impl<T, R> Automaton<T, R>
+where
+    T: Clone + PartialEq,
+    R: FnMut(&T, &[&T]) -> T,
+{
+    /// `rule` gets each cell along with its neighbours in the directions of
+    /// `neighbourhood` that are inside the grid, in that order, and returns the cell's
+    /// next value. The neighbourhood is usually [`Direction::cardinal`] or
+    /// [`Direction::all`], and follows the grid's [`Topology`](crate::grid::Topology).
+    pub fn new(grid: Grid<T>, neighbourhood: impl IntoIterator<Item = Direction>, rule: R) -> Self {
+        Self {
+            next: grid.clone(),
+            current: grid,
+            neighbourhood: neighbourhood.into_iter().collect(),
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.current
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.current
+    }
+
+    /// Number of steps taken so far.
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    /// Advance one generation, returns whether any cell changed.
+    pub fn step(&mut self) -> bool {
+        let changed = apply(
+            &self.current,
+            &mut self.next,
+            &self.neighbourhood,
+            &mut self.rule,
+        );
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+        changed
+    }
+
+    pub fn run(&mut self, generations: usize) -> &Grid<T> {
+        (0..generations).for_each(|_| {
+            self.step();
+        });
+        &self.current
+    }
+
+    /// Step until a generation is the same as the one before it, and return the
+    /// generation of that fixed point. Never returns if the automaton oscillates, use
+    /// [`Automaton::run_until_cycle`] for those.
+    pub fn run_until_stable(&mut self) -> usize {
+        while self.step() {}
+        self.generation - 1
+    }
+
+    /// Step until a generation repeats an earlier one, stopping on the first repeat.
+    pub fn run_until_cycle(&mut self) -> Cycle
+    where
+        T: Hash + Eq,
+    {
+        let mut seen = HashMap::new();
+        loop {
+            if let Some(start) = seen.insert(self.current.clone(), self.generation) {
+                return Cycle {
+                    start,
+                    length: self.generation - start,
+                };
+            }
+            self.step();
+        }
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: Clone + PartialEq,
+{
+    /// Apply `rule` to every cell at once and return the next generation, see
+    /// [`Automaton::new`] for the arguments and [`Automaton`] to run many generations.
+    pub fn step_automaton(
+        &self,
+        neighbourhood: &[Direction],
+        mut rule: impl FnMut(&T, &[&T]) -> T,
+    ) -> Grid<T> {
+        let mut next = self.clone();
+        apply(self, &mut next, neighbourhood, &mut rule);
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn life(cell: &char, neighbours: &[&char]) -> char {
+        match (cell, neighbours.iter().filter(|&&&c| c == '#').count()) {
+            ('#', 2 | 3) | (_, 3) => '#',
+            _ => '.',
+        }
+    }
+
+    #[test]
+    fn test_blinker_cycles() {
+        let grid = Grid::parse_chars(".....\n..#..\n..#..\n..#..\n.....\n").unwrap();
+        let next = grid.step_automaton(&Direction::all(), life);
+        assert_eq!(next.rows().nth(2), Some(&['.', '#', '#', '#', '.'][..]));
+
+        let mut automaton = Automaton::new(grid.clone(), Direction::all(), life);
+        let cycle = automaton.run_until_cycle();
+        assert_eq!(
+            cycle,
+            Cycle {
+                start: 0,
+                length: 2
+            }
+        );
+        assert_eq!(cycle.equivalent(1001), 1);
+        assert_eq!(automaton.run(2), &grid);
+        assert_eq!(automaton.generation(), 4);
+    }
+
+    #[test]
+    fn test_spread_until_stable() {
+        let grid = Grid::parse_chars(indoc! {"
+            o...
+            .##.
+            ....
+        "})
+        .unwrap();
+        let mut automaton = Automaton::new(grid, Direction::cardinal(), |&cell, neighbours| {
+            if cell == '.' && neighbours.contains(&&'o') {
+                'o'
+            } else {
+                cell
+            }
+        });
+        assert_eq!(automaton.run_until_stable(), 5);
+        assert!(automaton.grid().iter().all(|(_, &c)| c != '.'));
+        assert_eq!(
+            automaton.run_until_cycle(),
+            Cycle {
+                start: 6,
+                length: 1
+            }
+        );
+    }
+}
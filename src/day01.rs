@@ -2,47 +2,43 @@ use std::collections::HashMap;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
-use nom::{
-    bytes::complete::tag,
-    character::complete::{self, newline},
-    error::Error,
-    multi::separated_list1,
-    sequence::separated_pair,
-};
+use nom::{bytes::complete::tag, sequence::separated_pair};
 
-use crate::convert_error_to_owned;
+use crate::{
+    error::{self, checked_sum, AocError},
+    parse::{lines, unsigned},
+};
 
 #[aoc_generator(day01)]
-pub fn input_generator(input: &str) -> Result<Vec<(u64, u64)>, nom::Err<Error<String>>> {
-    separated_list1(
-        newline,
-        separated_pair(complete::u64, tag("   "), complete::u64),
-    )(input)
-    .map(|(_, o)| o)
-    .map_err(convert_error_to_owned)
+pub fn input_generator(input: &str) -> Result<Vec<(u64, u64)>, AocError> {
+    error::parse(
+        input,
+        "location ID pairs",
+        lines(separated_pair(unsigned, tag("   "), unsigned)),
+    )
 }
 
 #[aoc(day01, part1)]
-fn part1(input: &[(u64, u64)]) -> u64 {
+pub(crate) fn part1(input: &[(u64, u64)]) -> Result<u64, AocError> {
     let (mut left, mut right): (Vec<u64>, Vec<u64>) = input.iter().copied().unzip();
     left.sort_unstable();
     right.sort_unstable();
-    left.into_iter()
-        .zip(right)
-        .map(|(l, r)| l.abs_diff(r))
-        .sum()
+    checked_sum(left.into_iter().zip(right).map(|(l, r)| l.abs_diff(r)))
 }
 
 #[aoc(day01, part2)]
-fn part2(input: &[(u64, u64)]) -> u64 {
+pub(crate) fn part2(input: &[(u64, u64)]) -> Result<u64, AocError> {
     let (left, raw_right): (Vec<u64>, Vec<u64>) = input.iter().copied().unzip();
     let mut right = HashMap::new();
     raw_right
         .into_iter()
-        .for_each(|v| *right.entry(v).or_insert(0) += 1);
-    left.into_iter()
-        .filter_map(|n| right.get(&n).map(|v| v * n))
-        .sum()
+        .for_each(|v| *right.entry(v).or_insert(0u64) += 1);
+    let similarities = left
+        .into_iter()
+        .filter_map(|n| right.get(&n).map(|v| v.checked_mul(n)))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| AocError::invalid("similarity score overflows"))?;
+    checked_sum(similarities)
 }
 
 #[cfg(test)]
@@ -50,7 +46,7 @@ mod tests {
     use super::*;
     use indoc::indoc;
 
-    const INPUT: &'static str = indoc!(
+    const INPUT: &str = indoc!(
         "
         3   4
         4   3
@@ -63,14 +59,36 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let values = input_generator(&INPUT).unwrap();
+        let values = input_generator(INPUT).unwrap();
         assert_eq!(values.len(), 6);
-        assert_eq!(part1(&values), 11);
+        assert_eq!(part1(&values), Ok(11));
     }
 
     #[test]
     fn test_part_2() {
-        let values = input_generator(&INPUT).unwrap();
-        assert_eq!(part2(&values), 31);
+        let values = input_generator(INPUT).unwrap();
+        assert_eq!(part2(&values), Ok(31));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let error = |input| input_generator(input).unwrap_err().to_string();
+        assert_eq!(
+            error("3   4\n4\n"),
+            "line 2, column 2: unexpected text\n4\n ^"
+        );
+        assert_eq!(
+            error("3   4\n4   x\n"),
+            "line 2, column 5: expected a number\n4   x\n    ^"
+        );
+        assert_eq!(
+            error("3   4\n99999999999999999999   3\n"),
+            "line 2, column 1: number too large\n99999999999999999999   3\n^"
+        );
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        error::assert_no_panic(INPUT, input_generator, &[&part1, &part2]);
     }
 }
@@ -1,61 +1,49 @@
 use std::cmp::Ordering;
 
 use aoc_runner_derive::{aoc, aoc_generator};
+use nom::character::complete::char;
 
-use nom::{
-    bytes::complete::tag,
-    character::complete::{self, newline},
-    error::Error,
-    multi::separated_list1,
+use crate::{
+    error::{self, AocError},
+    parse::{lines, signed_list},
 };
 
-use crate::convert_error_to_owned;
-
 #[aoc_generator(day02)]
-pub fn input_generator(input: &str) -> Result<Vec<Vec<i64>>, nom::Err<Error<String>>> {
-    separated_list1(newline, separated_list1(tag(" "), complete::i64))(input)
-        .map(|(_, v)| v)
-        .map_err(convert_error_to_owned)
+pub fn input_generator(input: &str) -> Result<Vec<Vec<i64>>, AocError> {
+    error::parse(input, "reports", lines(signed_list(char(' '))))
 }
 
-#[aoc(day02, part1)]
-fn count_safe(input: &[Vec<i64>]) -> usize {
-    input
-        .iter()
-        .filter(|line| {
-            line.windows(2)
-                .map(|pair| (pair[0].cmp(&pair[1]), pair[0].abs_diff(pair[1])))
-                .try_reduce(|acc, cmp| {
-                    if acc.0 == cmp.0 && cmp.0 != Ordering::Equal && acc.1 <= 3 && cmp.1 <= 3 {
-                        Some(cmp)
-                    } else {
-                        None
-                    }
-                })
-                .is_some()
+fn is_safe(line: &[i64]) -> bool {
+    line.windows(2)
+        .map(|pair| (pair[0].cmp(&pair[1]), pair[0].abs_diff(pair[1])))
+        .try_reduce(|acc, cmp| {
+            if acc.0 == cmp.0 && cmp.0 != Ordering::Equal && acc.1 <= 3 && cmp.1 <= 3 {
+                Some(cmp)
+            } else {
+                None
+            }
         })
-        .count()
+        .is_some()
+}
+
+#[aoc(day02, part1)]
+pub(crate) fn count_safe(input: &[Vec<i64>]) -> Result<usize, AocError> {
+    Ok(input.iter().filter(|line| is_safe(line)).count())
 }
 
 #[aoc(day02, part2)]
-fn count_dampner_safe(input: &[Vec<i64>]) -> usize {
-    input
+pub(crate) fn count_dampner_safe(input: &[Vec<i64>]) -> Result<usize, AocError> {
+    Ok(input
         .iter()
         .filter(|line| {
-            if count_safe(&[line.to_vec()]) == 1 {
-                true
-            } else {
-                for index in 0..line.len() {
+            is_safe(line)
+                || (0..line.len()).any(|index| {
                     let mut line = line.to_vec();
                     line.remove(index);
-                    if count_safe(&[line]) == 1 {
-                        return true;
-                    }
-                }
-                false
-            }
+                    is_safe(&line)
+                })
         })
-        .count()
+        .count())
 }
 
 #[cfg(test)]
@@ -76,15 +64,37 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let values = input_generator(&INPUT).unwrap();
+        let values = input_generator(INPUT).unwrap();
         assert_eq!(values.len(), 8);
-        assert_eq!(count_safe(&values), 2);
+        assert_eq!(count_safe(&values), Ok(2));
     }
 
     #[test]
     fn test_part_2() {
-        let values = input_generator(&INPUT).unwrap();
+        let values = input_generator(INPUT).unwrap();
         assert_eq!(values.len(), 8);
-        assert_eq!(count_dampner_safe(&values), 6);
+        assert_eq!(count_dampner_safe(&values), Ok(6));
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let error = |input| input_generator(input).unwrap_err().to_string();
+        assert_eq!(
+            error("1 2 3\n4 5\n6 -\n"),
+            "line 3, column 4: expected a number\n6 -\n   ^"
+        );
+        assert_eq!(
+            error("1 2 3\n4 5 x\n"),
+            "line 2, column 5: expected a number\n4 5 x\n    ^"
+        );
+        assert_eq!(
+            error("1 2 3\n4 99999999999999999999\n"),
+            "line 2, column 3: number too large\n4 99999999999999999999\n  ^"
+        );
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        error::assert_no_panic(INPUT, input_generator, &[&count_safe, &count_dampner_safe]);
     }
 }
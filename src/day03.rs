@@ -7,6 +7,8 @@ use nom::{
     sequence::{delimited, separated_pair},
 };
 
+use crate::error::{checked_sum, AocError};
+
 fn mul(input: &str) -> nom::IResult<&str, (u64, u64)> {
     delimited(
         tag("mul("),
@@ -20,33 +22,45 @@ fn enabled_tag(input: &str) -> nom::IResult<&str, bool> {
     Ok((i, string == "do()"))
 }
 
+/// Drop the first character, corrupted memory may hold any character.
+fn skip_char(input: &str) -> &str {
+    let mut chars = input.chars();
+    chars.next();
+    chars.as_str()
+}
+
+fn product(a: u64, b: u64) -> Result<u64, AocError> {
+    a.checked_mul(b)
+        .ok_or_else(|| AocError::invalid(format!("mul({},{}) overflows", a, b)))
+}
+
 #[aoc(day03, part1)]
-fn part1(mut input: &str) -> u64 {
-    let mut ans = 0;
+pub(crate) fn part1(mut input: &str) -> Result<u64, AocError> {
+    let mut products = Vec::new();
     while !input.is_empty() {
         match mul(input) {
             Ok((i, (a, b))) => {
                 input = i;
-                ans += a * b;
+                products.push(product(a, b)?);
             }
             Err(_) => {
-                input = &input[1..];
+                input = skip_char(input);
             }
         }
     }
-    ans
+    checked_sum(products)
 }
 
 #[aoc(day03, part2)]
-fn part2(mut input: &str) -> u64 {
-    let mut ans = 0;
+pub(crate) fn part2(mut input: &str) -> Result<u64, AocError> {
+    let mut products = Vec::new();
     let mut enabled = true;
     while !input.is_empty() {
         match mul(input) {
             Ok((i, (a, b))) => {
                 input = i;
                 if enabled {
-                    ans += a * b;
+                    products.push(product(a, b)?);
                 }
             }
             Err(_) => {
@@ -54,32 +68,40 @@ fn part2(mut input: &str) -> u64 {
                     input = i;
                     enabled = e;
                 } else {
-                    input = &input[1..];
+                    input = skip_char(input);
                 }
             }
         }
     }
-    ans
+    checked_sum(products)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const INPUT1: &'static str =
-        "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
-    const INPUT2: &'static str =
+    const INPUT1: &str = "xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))";
+    const INPUT2: &str =
         "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
 
     #[test]
     fn test_part_1() {
-        let value = part1(&INPUT1);
-        assert_eq!(value, 161);
+        let value = part1(INPUT1);
+        assert_eq!(value, Ok(161));
     }
 
     #[test]
     fn test_part_2() {
-        let value = part2(&INPUT2);
-        assert_eq!(value, 48);
+        let value = part2(INPUT2);
+        assert_eq!(value, Ok(48));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        crate::error::assert_no_panic(
+            INPUT2,
+            |input: &str| Ok::<_, AocError>(input.to_string()),
+            &[&part1, &part2],
+        );
     }
 }
@@ -2,59 +2,37 @@ use std::ops::Not;
 
 use aoc_runner_derive::aoc;
 
-use crate::grid::{Direction, Grid};
+use crate::{
+    error::AocError,
+    grid::{Direction, Grid},
+};
 
-pub fn input_generator<const N: usize>(input: &str) -> Result<Grid<N, char>, String> {
-    let mut grid = Grid::default();
-    let mut count = 0;
-    input.lines().enumerate().for_each(|(line_nr, line)| {
-        line.chars().enumerate().for_each(|(char_nr, c)| {
-            grid[(char_nr, line_nr)] = c;
-            count += 1;
-        })
-    });
-    if count == N * N {
-        Ok(grid)
-    } else {
-        Err(format!(
-            "Parsed: {} characters, expected: {} characters",
-            count,
-            N * N
-        ))
-    }
+pub fn input_generator(input: &str) -> Result<Grid<char>, AocError> {
+    Grid::parse_chars(input).map_err(|e| AocError::from_grid(input, e))
 }
 
-fn part1<const N: usize>(grid: &Grid<N, char>) -> usize {
-    let directions = Direction::all();
-    let search_for = ['X', 'M', 'A', 'S'];
-    grid.iter()
-        .filter(|(_, c)| *c == &'X')
-        .map(|(pos, _)| {
-            directions
-                .iter()
-                .filter(|dir| {
-                    let mut search_pos = Some(pos);
-                    let valid = search_for
-                        .iter()
-                        .map(|search| {
-                            let found = grid.get(search_pos?)?;
-                            search_pos = dir.step(search_pos?, N);
-                            Some(found == search)
-                        })
-                        .all(|x| x == Some(true));
-                    valid
-                })
-                .count()
-        })
-        .sum()
+fn part1(grid: &Grid<char>) -> usize {
+    [
+        Direction::East,
+        Direction::South,
+        Direction::SouthEast,
+        Direction::SouthWest,
+    ]
+    .into_iter()
+    .flat_map(|direction| grid.lines(direction))
+    .map(|line| {
+        let line: String = line.map(|(_, &c)| c).collect();
+        line.matches("XMAS").count() + line.matches("SAMX").count()
+    })
+    .sum()
 }
 
 #[aoc(day04, part1)]
-fn solve_part1(input: &str) -> Result<usize, String> {
-    Ok(part1(&input_generator::<140>(input)?))
+pub(crate) fn solve_part1(input: &str) -> Result<usize, AocError> {
+    Ok(part1(&input_generator(input)?))
 }
 
-fn part2<const N: usize>(grid: &Grid<N, char>) -> usize {
+fn part2(grid: &Grid<char>) -> usize {
     let directions = [Direction::NorthEast, Direction::SouthEast];
     grid.iter()
         .filter(|(_, c)| *c == &'A')
@@ -62,8 +40,8 @@ fn part2<const N: usize>(grid: &Grid<N, char>) -> usize {
             directions
                 .iter()
                 .map(|dir| {
-                    let direction = grid.get(dir.step(*pos, N)?)?;
-                    let opposite = grid.get(dir.not().step(*pos, N)?)?;
+                    let direction = grid.get(dir.step(*pos, grid.dimensions())?)?;
+                    let opposite = grid.get(dir.not().step(*pos, grid.dimensions())?)?;
                     Some(
                         direction == &'M' && opposite == &'S'
                             || direction == &'S' && opposite == &'M',
@@ -75,8 +53,8 @@ fn part2<const N: usize>(grid: &Grid<N, char>) -> usize {
 }
 
 #[aoc(day04, part2)]
-fn solve_part2(input: &str) -> Result<usize, String> {
-    Ok(part2(&input_generator::<140>(input)?))
+pub(crate) fn solve_part2(input: &str) -> Result<usize, AocError> {
+    Ok(part2(&input_generator(input)?))
 }
 
 #[cfg(test)]
@@ -84,7 +62,7 @@ mod tests {
     use super::*;
     use indoc::indoc;
 
-    const INPUT: &'static str = indoc!(
+    const INPUT: &str = indoc!(
         "
         MMMSXXMASM
         MSAMXMSMSA
@@ -101,13 +79,22 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let parsed = input_generator::<10>(INPUT).unwrap();
+        let parsed = input_generator(INPUT).unwrap();
         assert_eq!(part1(&parsed), 18);
     }
 
     #[test]
     fn test_part_2() {
-        let parsed = input_generator::<10>(INPUT).unwrap();
+        let parsed = input_generator(INPUT).unwrap();
         assert_eq!(part2(&parsed), 9);
     }
+
+    #[test]
+    fn test_malformed_input() {
+        crate::error::assert_no_panic(
+            INPUT,
+            |input: &str| Ok::<_, AocError>(input.to_string()),
+            &[&solve_part1, &solve_part2],
+        );
+    }
 }
@@ -1,49 +1,39 @@
 use indexmap::IndexSet;
-use nom::{
-    bytes::complete::tag,
-    character::complete::{self, newline},
-    combinator::iterator,
-    error::Error,
-    multi::separated_list1,
-    sequence::{separated_pair, terminated},
-    IResult,
-};
+use nom::{bytes::complete::tag, character::complete::char, sequence::separated_pair};
 
-use crate::convert_error_to_owned;
+use crate::{
+    error::{self, checked_sum, AocError, ParseResult, Span},
+    parse::{blank_line, lines, unsigned, unsigned_list},
+};
 
 #[derive(Debug)]
-struct PageSetup {
+pub(crate) struct PageSetup {
     rule: IndexSet<(u64, u64)>,
     produce: Vec<IndexSet<u64>>,
 }
 
-fn parse_pair(input: &str) -> IResult<&str, (u64, u64)> {
-    separated_pair(complete::u64, tag("|"), complete::u64)(input)
-}
-
-fn parse_ordering_rules(input: &str) -> IResult<&str, IndexSet<(u64, u64)>> {
-    let mut ordering_iterator = iterator(input, terminated(parse_pair, newline));
-    let set = ordering_iterator.collect();
-    let res = ordering_iterator.finish();
-    res.map(move |(s, _)| (s, set))
-}
-
-fn parse_produce_line(input: &str) -> IResult<&str, IndexSet<u64>> {
-    separated_list1(tag(","), complete::u64)(input).map(|(s, n)| (s, n.into_iter().collect()))
-}
-
-fn parse_produce(input: &str) -> IResult<&str, Vec<IndexSet<u64>>> {
-    separated_list1(newline, parse_produce_line)(input)
-}
-
-fn parse_input(input: &str) -> Result<PageSetup, nom::Err<Error<&str>>> {
-    separated_pair(parse_ordering_rules, newline, parse_produce)(input)
-        .map(|(_, (rule, produce))| PageSetup { rule, produce })
+fn parse_pair(input: Span) -> ParseResult<(u64, u64)> {
+    separated_pair(unsigned, tag("|"), unsigned)(input)
 }
 
 #[aoc_generator(day05)]
-fn parse_actual_input(input: &str) -> Result<PageSetup, nom::Err<Error<String>>> {
-    parse_input(input).map_err(convert_error_to_owned)
+pub(crate) fn parse_input(input: &str) -> Result<PageSetup, AocError> {
+    error::parse(
+        input,
+        "ordering rules and updates",
+        separated_pair(
+            lines(parse_pair),
+            blank_line,
+            lines(unsigned_list(char(','))),
+        ),
+    )
+    .map(|(rule, produce)| PageSetup {
+        rule: rule.into_iter().collect(),
+        produce: produce
+            .into_iter()
+            .map(|update| update.into_iter().collect())
+            .collect(),
+    })
 }
 
 fn page_follows_rules(pages: &PageSetup, p: &IndexSet<u64>) -> bool {
@@ -58,45 +48,60 @@ fn page_follows_rules(pages: &PageSetup, p: &IndexSet<u64>) -> bool {
         .all(|n| n != Some(false))
 }
 
+fn middle_page(update: &IndexSet<u64>) -> Result<u64, AocError> {
+    update
+        .get_index(update.len() / 2)
+        .copied()
+        .ok_or_else(|| AocError::invalid("empty update"))
+}
+
 #[aoc(day05, part1)]
-fn part_1(pages: &PageSetup) -> u64 {
-    pages
+pub(crate) fn part_1(pages: &PageSetup) -> Result<u64, AocError> {
+    let middles = pages
         .produce
         .iter()
         .filter(|p| page_follows_rules(pages, p))
-        .map(|n| n.get_index(n.len() / 2).unwrap())
-        .sum()
+        .map(middle_page)
+        .collect::<Result<Vec<_>, _>>()?;
+    checked_sum(middles)
+}
+
+/// Swap pages until every rule holds. Each swap of a consistent ordering removes an
+/// inversion, so rules that contradict each other are given up on after enough passes.
+fn reorder(pages: &PageSetup, update: &IndexSet<u64>) -> Result<IndexSet<u64>, AocError> {
+    let mut line = update.clone();
+    let passes = line.len() * line.len() + 1;
+    for rule in pages.rule.iter().cycle().take(pages.rule.len() * passes) {
+        let start_index = match line.get_full(&rule.0) {
+            Some(s) => s.0,
+            None => continue,
+        };
+        let end_index = match line.get_full(&rule.1) {
+            Some(s) => s.0,
+            None => continue,
+        };
+        if start_index > end_index {
+            line.swap_indices(start_index, end_index);
+            if page_follows_rules(pages, &line) {
+                return Ok(line);
+            }
+        }
+    }
+    Err(AocError::invalid(format!(
+        "the rules give no order for update {:?}",
+        update
+    )))
 }
 
 #[aoc(day05, part2)]
-fn part_2(pages: &PageSetup) -> u64 {
-    pages
+pub(crate) fn part_2(pages: &PageSetup) -> Result<u64, AocError> {
+    let middles = pages
         .produce
         .iter()
         .filter(|p| !page_follows_rules(pages, p))
-        .map(|p| {
-            let mut line = p.clone();
-            let mut rules = pages.rule.iter().cycle();
-            loop {
-                let rule = rules.next().unwrap();
-                let start_index = match line.get_full(&rule.0) {
-                    Some(s) => s.0,
-                    None => continue,
-                };
-                let end_index = match line.get_full(&rule.1) {
-                    Some(s) => s.0,
-                    None => continue,
-                };
-                if start_index > end_index {
-                    line.swap_indices(start_index, end_index);
-                    if page_follows_rules(pages, &line) {
-                        break line;
-                    }
-                }
-            }
-        })
-        .map(|n| *n.get_index(n.len() / 2).unwrap())
-        .sum()
+        .map(|p| middle_page(&reorder(pages, p)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    checked_sum(middles)
 }
 
 #[cfg(test)]
@@ -105,7 +110,7 @@ mod tests {
 
     use super::*;
 
-    const INPUT: &'static str = indoc! {"
+    const INPUT: &str = indoc! {"
         47|53
         97|13
         97|61
@@ -138,20 +143,31 @@ mod tests {
 
     #[test]
     fn test_parsing() {
-        parse_input(&INPUT).unwrap();
+        parse_input(INPUT).unwrap();
     }
 
     #[test]
     fn test_part_1() {
-        let input = parse_input(&INPUT).unwrap();
+        let input = parse_input(INPUT).unwrap();
         let ans = part_1(&input);
-        assert_eq!(ans, 143);
+        assert_eq!(ans, Ok(143));
     }
 
     #[test]
     fn test_part_2() {
-        let input = parse_input(&INPUT).unwrap();
+        let input = parse_input(INPUT).unwrap();
         let ans = part_2(&input);
-        assert_eq!(ans, 123);
+        assert_eq!(ans, Ok(123));
+    }
+
+    #[test]
+    fn test_contradicting_rules() {
+        let input = parse_input("1|2\n2|1\n\n2,1\n").unwrap();
+        assert!(part_2(&input).is_err());
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        error::assert_no_panic(INPUT, parse_input, &[&part_1, &part_2]);
     }
 }
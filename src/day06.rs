@@ -1,101 +1,82 @@
-use std::{cell::RefCell, collections::HashSet};
-
-use crate::grid::Direction;
-
-type Point = (usize, usize);
-
-struct Maze {
-    blocks: HashSet<Point>,
-    start_pos: Point,
-    size: Point,
+use crate::{
+    error::AocError,
+    grid::{BitGrid, Direction, DirectionSet, Grid, GridLike},
+    point::Point,
+};
+
+pub(crate) struct Maze {
+    blocks: BitGrid,
+    start_pos: Point<usize>,
+    start_direction: Direction,
 }
 
 #[aoc_generator(day06)]
-fn parse(input: &str) -> Result<Maze, String> {
-    let start_pos = RefCell::new(None);
-    let start_pos = &start_pos;
-    let mut width = 0;
-    let mut height = 0;
-
-    let blocks = input
-        .lines()
-        .inspect(|line| {
-            width = width.max(line.len());
-            height += 1;
-        })
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.chars().enumerate().filter_map(move |(x, c)| {
-                if c == '^' {
-                    *start_pos.borrow_mut() = Some((x, y));
-                    None
-                } else if c == '#' {
-                    Some((x, y))
-                } else {
-                    None
-                }
-            })
-        })
-        .collect::<HashSet<_>>();
+pub(crate) fn parse(input: &str) -> Result<Maze, AocError> {
+    let grid = Grid::parse_chars(input).map_err(|e| AocError::from_grid(input, e))?;
+    let (start_pos, start_direction) = grid
+        .iter()
+        .find_map(|(pos, &c)| Some((pos.into(), Direction::from_arrow(c)?)))
+        .ok_or_else(|| AocError::invalid("No start position found"))?;
 
     Ok(Maze {
-        blocks,
-        start_pos: start_pos
-            .clone()
-            .into_inner()
-            .ok_or_else(|| "No start position found".to_string())?,
-        size: (width, height),
+        blocks: grid.to_bits(|&c| c == '#'),
+        start_pos,
+        start_direction,
     })
 }
 
-fn get_steps(maze: &Maze) -> Option<usize> {
-    let mut direction = Direction::North;
+/// The positions visited before leaving the map, `None` if the guard walks in a loop.
+fn walk(maze: &Maze) -> Option<BitGrid> {
+    let (width, height) = maze.blocks.dimensions();
+    let mut direction = maze.start_direction;
     let mut pos = maze.start_pos;
-    let mut visited = HashSet::new();
-    let mut loop_finder = HashSet::new();
+    let mut visited = BitGrid::new(width, height);
+    let mut headings = Grid::<DirectionSet>::new(width, height);
     visited.insert(pos);
-    loop_finder.insert((pos, direction));
-    assert_eq!(maze.size.0, maze.size.1);
-    let size = maze.size.0;
+    headings[pos].insert(direction);
 
-    while let Some(next_step) = direction.step(pos, size) {
-        if maze.blocks.contains(&next_step) {
+    while let Some(next_step) = direction.step(pos, maze.blocks.dimensions()) {
+        if maze.blocks.contains(next_step) {
             direction = direction.right_turn();
         } else {
             visited.insert(next_step);
             pos = next_step;
         }
 
-        if !loop_finder.insert((pos, direction)) {
+        if !headings[pos].insert(direction) {
             return None;
         }
     }
 
-    Some(visited.len())
+    Some(visited)
 }
 
 #[aoc(day06, part1)]
-fn part_1(maze: &Maze) -> usize {
-    get_steps(maze).unwrap()
+pub(crate) fn part_1(maze: &Maze) -> Result<usize, AocError> {
+    walk(maze)
+        .map(|visited| visited.count_ones())
+        .ok_or_else(|| AocError::invalid("the guard never leaves the map"))
 }
 
+/// Only a block on the guard's own path can change where it goes, so those are the only
+/// cells tried, one at a time on a single copy of the map.
 #[aoc(day06, part2)]
-fn part_2(maze: &Maze) -> usize {
-    (0..maze.size.1)
-        .flat_map(|y| (0..maze.size.0).map(move |x| (x, y)))
-        .filter(|&pos| !maze.blocks.contains(&pos))
-        .filter(|&pos| pos != maze.start_pos)
+pub(crate) fn part_2(maze: &Maze) -> Result<usize, AocError> {
+    let path = walk(maze).ok_or_else(|| AocError::invalid("the guard never leaves the map"))?;
+    let mut blocked = Maze {
+        blocks: maze.blocks.clone(),
+        ..*maze
+    };
+    Ok(path
+        .ones()
+        .filter(|&pos| pos != maze.start_pos.into())
         .filter(|&pos| {
-            let mut m = maze.blocks.clone();
-            m.insert(pos);
-            get_steps(&Maze {
-                blocks: m,
-                start_pos: maze.start_pos,
-                size: maze.size,
-            })
-            .is_none()
+            blocked.blocks.insert(pos);
+            let loops = walk(&blocked).is_none();
+            blocked.blocks.remove(pos);
+            loops
         })
-        .count()
+        .count())
 }
 
 #[cfg(test)]
@@ -120,12 +101,29 @@ mod tests {
     #[test]
     fn test_part_1() {
         let result = parse(TEST_INPUT).unwrap();
-        assert_eq!(part_1(&result), 41);
+        assert_eq!(part_1(&result), Ok(41));
     }
 
     #[test]
     fn test_part_2() {
         let result = parse(TEST_INPUT).unwrap();
-        assert_eq!(part_2(&result), 6);
+        assert_eq!(part_2(&result), Ok(6));
+    }
+
+    #[test]
+    fn test_start_facing_other_directions() {
+        let maze = Maze {
+            start_direction: Direction::West,
+            ..parse(TEST_INPUT).unwrap()
+        };
+        let input = TEST_INPUT.replace('^', "<");
+        let parsed = parse(&input).unwrap();
+        assert_eq!(parsed.start_direction, Direction::West);
+        assert_eq!(part_1(&parsed), part_1(&maze));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        crate::error::assert_no_panic(TEST_INPUT, parse, &[&part_1, &part_2]);
     }
 }
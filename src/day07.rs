@@ -1,123 +1,77 @@
-use std::collections::VecDeque;
+use nom::{bytes::complete::tag, character::complete::char, sequence::separated_pair};
 
-use nom::{
-    bytes::complete::tag, character::complete, multi::separated_list1, sequence::separated_pair,
-    IResult,
+use crate::{
+    error::{self, checked_sum, AocError, ParseResult, Span},
+    parse::{lines, unsigned, unsigned_list},
 };
 
-struct Equation {
+#[derive(Debug)]
+pub(crate) struct Equation {
     target: u64,
-    numbers: VecDeque<u64>,
+    numbers: Vec<u64>,
 }
 
-fn parse_equation(input: &str) -> IResult<&str, Equation> {
-    separated_pair(
-        complete::u64,
-        tag(": "),
-        separated_list1(tag(" "), complete::u64),
-    )(input)
-    .map(|(rest, (target, numbers))| {
-        (
-            rest,
-            Equation {
-                target,
-                numbers: numbers.into_iter().collect(),
-            },
-        )
-    })
-}
-
-fn parse_input(input: &str) -> IResult<&str, Vec<Equation>> {
-    separated_list1(complete::newline, parse_equation)(input)
+fn parse_equation(input: Span) -> ParseResult<Equation> {
+    separated_pair(unsigned, tag(": "), unsigned_list(char(' ')))(input)
+        .map(|(rest, (target, numbers))| (rest, Equation { target, numbers }))
 }
 
 #[aoc_generator(day07)]
-fn input_generator(input: &str) -> Vec<Equation> {
-    parse_input(input).unwrap().1
+pub(crate) fn input_generator(input: &str) -> Result<Vec<Equation>, AocError> {
+    error::parse(input, "equations", lines(parse_equation))
 }
 
-fn solvable_part1(equation: &Equation) -> bool {
-    let mut values = equation.numbers.clone();
-    let first = values.pop_front().unwrap();
-    if values.is_empty() {
-        return first == equation.target;
-    }
-    if first > equation.target {
+/// Whether the numbers after `value` can be folded into the target, left to right.
+/// Only multiplying by zero makes the value smaller, so once it is past the target with
+/// no zeros left the branch is given up on.
+fn solvable(target: u64, value: u64, rest: &[u64], concatenation: bool) -> bool {
+    if value > target && !rest.contains(&0) {
         return false;
     }
-    let second = values.front().unwrap();
-    let mut adding = values.clone();
-    *adding.front_mut().unwrap() = first + second;
-    if solvable_part1(&Equation {
-        target: equation.target,
-        numbers: adding,
-    }) {
-        true
-    } else {
-        *values.front_mut().unwrap() = first * second;
-        solvable_part1(&Equation {
-            target: equation.target,
-            numbers: values,
-        })
-    }
+    let Some((&next, rest)) = rest.split_first() else {
+        return value == target;
+    };
+    [
+        value.checked_add(next),
+        value.checked_mul(next),
+        concatenation.then(|| concatinate(value, next)).flatten(),
+    ]
+    .into_iter()
+    .flatten()
+    .any(|value| solvable(target, value, rest, concatenation))
 }
 
-fn solvable_part2(equation: &Equation) -> bool {
-    let mut values = equation.numbers.clone();
-    let first = values.pop_front().unwrap();
-    if values.is_empty() {
-        return first == equation.target;
-    }
-    if first > equation.target {
-        return false;
-    }
-    let second = values.front().unwrap();
-    let mut adding = values.clone();
-    *adding.front_mut().unwrap() = first + second;
-    if solvable_part2(&Equation {
-        target: equation.target,
-        numbers: adding,
-    }) {
-        true
-    } else {
-        let mut multiplying = values.clone();
-        *multiplying.front_mut().unwrap() = first * second;
-        if solvable_part2(&Equation {
-            target: equation.target,
-            numbers: multiplying,
-        }) {
-            true
-        } else {
-            *values.front_mut().unwrap() = concatinate(first, *second);
-            solvable_part2(&Equation {
-                target: equation.target,
-                numbers: values,
-            })
-        }
+fn solvable_equation(equation: &Equation, concatenation: bool) -> bool {
+    match equation.numbers.split_first() {
+        Some((&first, rest)) => solvable(equation.target, first, rest, concatenation),
+        None => false,
     }
 }
 
-fn concatinate(first: u64, second: u64) -> u64 {
-    let offset = second.ilog10() + 1;
-    first * 10u64.pow(offset) + second
+fn concatinate(first: u64, second: u64) -> Option<u64> {
+    let offset = second.checked_ilog10().unwrap_or(0) + 1;
+    first
+        .checked_mul(10u64.checked_pow(offset)?)?
+        .checked_add(second)
+}
+
+fn calibration(input: &[Equation], concatenation: bool) -> Result<u64, AocError> {
+    checked_sum(
+        input
+            .iter()
+            .filter(|equation| solvable_equation(equation, concatenation))
+            .map(|equation| equation.target),
+    )
 }
 
 #[aoc(day07, part1)]
-fn part1(input: &[Equation]) -> u64 {
-    input
-        .iter()
-        .filter(|equation| solvable_part1(equation))
-        .map(|equation| equation.target)
-        .sum()
+pub(crate) fn part1(input: &[Equation]) -> Result<u64, AocError> {
+    calibration(input, false)
 }
 
 #[aoc(day07, part2)]
-fn part2(input: &[Equation]) -> u64 {
-    input
-        .iter()
-        .filter(|equation| solvable_part2(equation))
-        .map(|equation| equation.target)
-        .sum()
+pub(crate) fn part2(input: &[Equation]) -> Result<u64, AocError> {
+    calibration(input, true)
 }
 
 #[cfg(test)]
@@ -139,19 +93,56 @@ mod test_day07 {
 
     #[test]
     fn test_parsing() {
-        let input = input_generator(INPUT);
+        let input = input_generator(INPUT).unwrap();
         assert_eq!(input.len(), 9);
         assert_eq!(input[0].target, 190);
         assert_eq!(input[0].numbers, vec![10, 19]);
     }
 
+    #[test]
+    fn test_truncated_line() {
+        assert_eq!(
+            input_generator("190: 10 19\n3267:\n")
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 5: unexpected text\n3267:\n    ^"
+        );
+    }
+
+    #[test]
+    fn test_bad_number() {
+        let error = |input| input_generator(input).unwrap_err().to_string();
+        assert_eq!(
+            error("190: 10 19\n3267: 81 x\n"),
+            "line 2, column 10: expected a number\n3267: 81 x\n         ^"
+        );
+        assert_eq!(
+            error("190: 10 19\n99999999999999999999: 1\n"),
+            "line 2, column 1: number too large\n99999999999999999999: 1\n^"
+        );
+    }
+
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&input_generator(INPUT)), 3749);
+        assert_eq!(part1(&input_generator(INPUT).unwrap()), Ok(3749));
     }
 
     #[test]
     fn test_part2() {
-        assert_eq!(part2(&input_generator(INPUT)), 11387);
+        assert_eq!(part2(&input_generator(INPUT).unwrap()), Ok(11387));
+    }
+
+    #[test]
+    fn test_zero_operand() {
+        let equations = input_generator("3: 5 0 3\n0: 5 0\n53: 5 0 3\n").unwrap();
+        assert!(solvable_equation(&equations[0], false));
+        assert!(solvable_equation(&equations[1], false));
+        assert!(!solvable_equation(&equations[2], false));
+        assert!(solvable_equation(&equations[2], true));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        error::assert_no_panic(INPUT, input_generator, &[&part1, &part2]);
     }
 }
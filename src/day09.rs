@@ -2,84 +2,94 @@ use aoc_runner_derive::{aoc, aoc_generator};
 
 use std::{
     collections::{HashMap, VecDeque},
-    iter::repeat,
+    iter::repeat_n,
 };
 
+use crate::error::{checked_sum, AocError};
+
 type Disk = Vec<Option<usize>>;
 type DiskSlice = [Option<usize>];
 
-#[aoc_generator(day09, part1)]
-fn parse_input(input: &str) -> Disk {
-    let mut value = true;
-    let mut bit_values = 0..;
+/// The size of every block on the disk map.
+fn digits(input: &str) -> Result<Vec<usize>, AocError> {
+    if input.trim_end().is_empty() {
+        return Err(AocError::at(input, 1, 1, "expected a disk map"));
+    }
     input
+        .trim_end()
         .chars()
-        .map(|c| c.to_digit(10).unwrap() as usize)
-        .flat_map(|d| {
-            let v = if value {
-                Some(bit_values.next().unwrap())
-            } else {
-                None
-            };
-            value = !value;
-            repeat(v).take(d)
+        .enumerate()
+        .map(|(column, c)| {
+            c.to_digit(10).map(|d| d as usize).ok_or_else(|| {
+                AocError::at(input, 1, column + 1, format!("{:?}: expected a digit", c))
+            })
         })
         .collect()
 }
 
+#[aoc_generator(day09, part1)]
+pub(crate) fn parse_input(input: &str) -> Result<Disk, AocError> {
+    Ok(digits(input)?
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, d)| repeat_n((index % 2 == 0).then_some(index / 2), d))
+        .collect())
+}
+
 #[derive(Debug, Clone, Copy)]
-struct Section {
+pub(crate) struct Section {
     start: usize,
     length: usize,
     id: usize,
 }
 
 impl Section {
-    fn score(&self) -> usize {
-        (self.start..self.start + self.length).sum::<usize>() * (self.id)
+    /// The checksum of the blocks in the section, `None` if it does not fit in a `usize`.
+    fn score(&self) -> Option<usize> {
+        (self.start..self.start.checked_add(self.length)?).try_fold(0usize, |sum, position| {
+            sum.checked_add(position.checked_mul(self.id)?)
+        })
     }
 }
 
 type Sections = (VecDeque<Section>, HashMap<usize, VecDeque<Section>>);
 
 #[aoc_generator(day09, part2)]
-fn parse_input_p2(input: &str) -> Sections {
+pub(crate) fn parse_input_p2(input: &str) -> Result<Sections, AocError> {
     let mut pos = 0;
-    input
-        .chars()
-        .map(|c| c.to_digit(10).unwrap() as usize)
-        .enumerate()
-        .fold(
-            (VecDeque::new(), HashMap::new()),
-            |(mut disk, mut free), (index, size)| {
-                let section = Section {
-                    start: pos,
-                    length: size,
-                    id: index / 2,
-                };
-                pos += size;
-                if index % 2 == 0 {
-                    disk.push_back(section);
-                } else {
-                    free.entry(size).or_default().push_back(section);
-                };
-                (disk, free)
-            },
-        )
+    Ok(digits(input)?.into_iter().enumerate().fold(
+        (VecDeque::new(), HashMap::new()),
+        |(mut disk, mut free), (index, size)| {
+            let section = Section {
+                start: pos,
+                length: size,
+                id: index / 2,
+            };
+            pos += size;
+            if index % 2 == 0 {
+                disk.push_back(section);
+            } else {
+                free.entry(size).or_default().push_back(section);
+            };
+            (disk, free)
+        },
+    ))
 }
 
 fn compact_disk_p1(d: &mut DiskSlice) {
+    // Everything from `right` onwards is already in place.
     let mut left = 0;
-    let mut right = d.len() - 1;
+    let mut right = d.len();
     while left < right {
-        if d[left].is_none() {
-            while d[right].is_none() {
-                right -= 1;
-            }
-            d.swap(left, right);
+        if d[left].is_some() {
+            left += 1;
+        } else if d[right - 1].is_none() {
+            right -= 1;
+        } else {
+            d.swap(left, right - 1);
+            left += 1;
             right -= 1;
         }
-        left += 1;
     }
 }
 
@@ -139,35 +149,33 @@ fn _compact_disk_p2(d: &mut DiskSlice) {
 }
 
 #[aoc(day09, part1)]
-fn part1(input: &DiskSlice) -> usize {
+pub(crate) fn part1(input: &DiskSlice) -> Result<usize, AocError> {
     let mut disk: Disk = input.to_vec();
     compact_disk_p1(&mut disk);
-    disk.into_iter()
+    let blocks = disk
+        .into_iter()
         .flatten()
         .enumerate()
-        .map(|(i, o)| i * o)
-        .sum()
+        .map(|(i, o)| i.checked_mul(o))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| AocError::invalid("a block checksum does not fit in an integer"))?;
+    checked_sum(blocks)
 }
 
 #[aoc(day09, part2)]
-fn part2(disk: &Sections) -> usize {
+pub(crate) fn part2(disk: &Sections) -> Result<usize, AocError> {
     let (mut disk, mut free) = disk.clone();
     for section in disk.iter_mut().rev() {
         let find_candidate = (section.length..=9)
             .filter_map(|size| free.get(&size).and_then(VecDeque::front))
             .copied()
             .min_by_key(|s| s.start);
-        let candidate = match find_candidate {
-            Some(s) => s,
-            None => {
-                continue;
-            }
+        let Some(candidate) = find_candidate
+            .and_then(|s| free.get_mut(&s.length))
+            .and_then(VecDeque::pop_front)
+        else {
+            continue;
         };
-        let candidate = free
-            .get_mut(&candidate.length)
-            .unwrap()
-            .pop_front()
-            .unwrap();
         if candidate.start > section.start {
             continue;
         }
@@ -176,7 +184,7 @@ fn part2(disk: &Sections) -> usize {
             let e = free.entry(candidate.length - section.length).or_default();
             let p = e
                 .binary_search_by_key(&(candidate.start + candidate.length), |s| s.start)
-                .unwrap_err();
+                .unwrap_or_else(|p| p);
             e.insert(
                 p,
                 Section {
@@ -187,7 +195,12 @@ fn part2(disk: &Sections) -> usize {
             );
         }
     }
-    disk.iter().map(Section::score).sum()
+    let sections = disk
+        .iter()
+        .map(Section::score)
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| AocError::invalid("a section checksum does not fit in an integer"))?;
+    checked_sum(sections)
 }
 
 #[cfg(test)]
@@ -204,15 +217,23 @@ mod test_day09 {
 
     #[test]
     fn test_parse() {
-        let parsed = parse_input(EXAMPLE);
+        let parsed = parse_input(EXAMPLE).unwrap();
         let expected = "00...111...2...333.44.5555.6666.777.888899";
         let parsed_str = print_disk(&parsed);
         assert_eq!(parsed_str, expected);
     }
 
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            parse_input("23x3\n").unwrap_err().to_string(),
+            "line 1, column 3: 'x': expected a digit\n23x3\n  ^"
+        );
+    }
+
     #[test]
     fn test_compact_p1() {
-        let mut parsed = parse_input(EXAMPLE);
+        let mut parsed = parse_input(EXAMPLE).unwrap();
         let expected = "0099811188827773336446555566..............";
         compact_disk_p1(&mut parsed);
         let parsed_str = print_disk(&parsed);
@@ -221,24 +242,46 @@ mod test_day09 {
 
     #[test]
     fn test_part1() {
-        let parsed = parse_input(EXAMPLE);
+        let parsed = parse_input(EXAMPLE).unwrap();
         let part1_result = part1(&parsed);
-        assert_eq!(part1_result, 1928);
+        assert_eq!(part1_result, Ok(1928));
     }
 
     #[test]
     fn test_part2() {
-        let parsed = parse_input_p2(EXAMPLE);
+        let parsed = parse_input_p2(EXAMPLE).unwrap();
         let part2_result = part2(&parsed);
-        assert_eq!(part2_result, 2858);
+        assert_eq!(part2_result, Ok(2858));
     }
 
     #[test]
     fn test_p2_value() {
         let correct = 6239783302560;
         //            6239783431260;
-        let parsed = parse_input_p2(include_str!("../input/2024/day9.txt").trim());
+        let parsed = parse_input_p2(include_str!("../input/2024/day9.txt").trim()).unwrap();
         let part2_result = part2(&parsed);
-        assert_eq!(part2_result, correct);
+        assert_eq!(part2_result, Ok(correct));
+    }
+
+    #[test]
+    fn test_overflowing_checksum() {
+        let section = Section {
+            start: usize::MAX / 2,
+            length: 2,
+            id: 3,
+        };
+        assert_eq!(section.score(), None);
+        assert_eq!(
+            part1(&[Some(0), Some(0), Some(usize::MAX)]),
+            Err(AocError::invalid(
+                "a block checksum does not fit in an integer"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        crate::error::assert_no_panic(EXAMPLE, parse_input, &[&part1]);
+        crate::error::assert_no_panic(EXAMPLE, parse_input_p2, &[&part2]);
     }
 }
@@ -1,88 +1,52 @@
-use std::collections::HashSet;
-
 use aoc_runner_derive::{aoc, aoc_generator};
 
-use crate::grid::{Direction, Grid};
+use crate::{
+    error::{self, checked_sum, AocError},
+    grid::{Direction, Grid},
+    parse::grid,
+    search::PathCounts,
+};
 
 #[aoc_generator(day10)]
-fn parse_input(input: &str) -> Result<Grid<55, usize>, String> {
+pub(crate) fn parse_input(input: &str) -> Result<Grid<usize>, AocError> {
     input_generator(input)
 }
 
-fn input_generator<const N: usize>(input: &str) -> Result<Grid<N, usize>, String> {
-    let mut grid = Grid::default();
-    let mut count = 0;
-    input.lines().enumerate().for_each(|(line_nr, line)| {
-        line.chars().enumerate().for_each(|(char_nr, c)| {
-            grid[(char_nr, line_nr)] = c.to_digit(10).unwrap() as usize;
-            count += 1;
-        })
-    });
-    if count == N * N {
-        Ok(grid)
-    } else {
-        Err(format!(
-            "Parsed: {} characters, expected: {} characters",
-            count,
-            N * N
-        ))
-    }
+fn input_generator(input: &str) -> Result<Grid<usize>, AocError> {
+    let height = |c: char| {
+        c.to_digit(10)
+            .map(|d| d as usize)
+            .ok_or("expected a height digit")
+    };
+    error::parse(input, "topographic map", grid(height))
 }
 
-fn step_to_nine<const N: usize>(
-    grid: &Grid<N, usize>,
-    pos: (usize, usize),
-    value: usize,
-    tail_heads: &mut HashSet<(usize, usize)>,
-) -> usize {
-    if grid[pos] == 9 && value == 9 {
-        tail_heads.insert(pos);
-        return 1;
-    } else if grid[pos] != value {
-        return 0;
-    }
-    Direction::cardinal()
-        .iter()
-        .filter_map(|d| d.step(pos, N))
-        .map(|dir| step_to_nine(grid, dir, value + 1, tail_heads))
-        .sum()
+fn trails(grid: &Grid<usize>) -> Result<PathCounts, AocError> {
+    grid.count_paths(&Direction::cardinal(), |&a, &b| b == a + 1, |&v| v == 9)
+        .ok_or_else(|| AocError::invalid("the trails lead around in a circle"))
 }
 
-fn step_to_nine_part2<const N: usize>(
-    grid: &Grid<N, usize>,
-    pos: (usize, usize),
-    value: usize,
-) -> usize {
-    if grid[pos] == 9 && value == 9 {
-        return 1;
-    } else if grid[pos] != value {
-        return 0;
-    }
-    Direction::cardinal()
-        .iter()
-        .filter_map(|d| d.step(pos, N))
-        .map(|dir| step_to_nine_part2(grid, dir, value + 1))
-        .sum()
+fn trailheads(grid: &Grid<usize>) -> impl Iterator<Item = (usize, usize)> + '_ {
+    grid.iter().filter(|(_, &v)| v == 0).map(|(pos, _)| pos)
 }
 
 #[aoc(day10, part1)]
-fn part1<const N: usize>(grid: &Grid<N, usize>) -> usize {
-    grid.iter()
-        .filter(|(_, &v)| v == 0)
-        .map(|(pos, _)| {
-            let mut set = HashSet::new();
-            step_to_nine(grid, pos, 0, &mut set);
-            set.len()
-        })
-        .sum()
+pub(crate) fn part1(grid: &Grid<usize>) -> Result<usize, AocError> {
+    let trails = trails(grid)?;
+    checked_sum(trailheads(grid).map(|pos| trails.sinks_from(pos)))
 }
 
 #[aoc(day10, part2)]
-fn part2<const N: usize>(grid: &Grid<N, usize>) -> usize {
-    grid.iter()
-        .filter(|(_, &v)| v == 0)
-        .map(|(pos, _)| step_to_nine_part2(grid, pos, 0))
-        .sum()
+pub(crate) fn part2(grid: &Grid<usize>) -> Result<usize, AocError> {
+    let trails = trails(grid)?;
+    let ratings = trailheads(grid)
+        .map(|pos| {
+            let paths = trails.paths_from(pos)?;
+            usize::try_from(paths).ok()
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| AocError::invalid("a trailhead rating does not fit in an integer"))?;
+    checked_sum(ratings)
 }
 
 #[cfg(test)]
@@ -104,20 +68,41 @@ mod tests_day10 {
 
     #[test]
     fn test_parse() {
-        let grid = input_generator::<8>(TEST_INPUT).unwrap();
+        let grid = input_generator(TEST_INPUT).unwrap();
         assert_eq!(grid[(0, 0)], 8);
         assert_eq!(grid[(7, 7)], 2);
     }
 
+    #[test]
+    fn test_parse_invalid() {
+        assert_eq!(
+            input_generator("0123\n01.3\n").unwrap_err().to_string(),
+            "line 2, column 3: '.': expected a height digit\n01.3\n  ^"
+        );
+        assert_eq!(
+            input_generator("0123\n012\n").unwrap_err().to_string(),
+            "line 2, column 4: expected 4 characters, found 3\n012\n   ^"
+        );
+        assert_eq!(
+            input_generator("0123\n\n0123\n").unwrap_err().to_string(),
+            "line 3, column 1: unexpected input after topographic map\n0123\n^"
+        );
+    }
+
     #[test]
     fn test_part1() {
-        let grid = input_generator::<8>(TEST_INPUT).unwrap();
-        assert_eq!(part1(&grid), 36);
+        let grid = input_generator(TEST_INPUT).unwrap();
+        assert_eq!(part1(&grid), Ok(36));
     }
 
     #[test]
     fn test_part2() {
-        let grid = input_generator::<8>(TEST_INPUT).unwrap();
-        assert_eq!(part2(&grid), 81);
+        let grid = input_generator(TEST_INPUT).unwrap();
+        assert_eq!(part2(&grid), Ok(81));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        crate::error::assert_no_panic(TEST_INPUT, input_generator, &[&part1, &part2]);
     }
 }
@@ -1,49 +1,53 @@
 use aoc_runner_derive::{aoc, aoc_generator};
-use nom::{
-    character::complete::{self, space1},
-    error::Error,
-    multi::separated_list1,
-};
+use nom::character::complete::space1;
 
-use crate::convert_error_to_owned;
+use crate::{
+    error::{self, checked_sum, AocError},
+    parse::unsigned_list,
+};
 
 use dp_macro::dp;
 
 #[aoc_generator(day11)]
-fn parse_input(input: &str) -> Result<Vec<u64>, nom::Err<Error<String>>> {
-    separated_list1(space1, complete::u64)(input)
-        .map_err(convert_error_to_owned)
-        .map(|(_, v)| v)
+pub(crate) fn parse_input(input: &str) -> Result<Vec<u64>, AocError> {
+    error::parse(input, "stones", unsigned_list(space1))
 }
 
+/// The number of stones `x` has become after `steps` blinks, `None` once a stone or
+/// the count no longer fits in a `u64`.
 #[dp]
-fn solve(x: u64, steps: u64) -> u64 {
+fn solve(x: u64, steps: u64) -> Option<u64> {
     if steps == 0 {
-        return 1;
+        return Some(1);
     } else if x == 0 {
         return solve(1, steps - 1);
     }
     let number_of_digits = x.ilog10() + 1;
-    if number_of_digits % 2 == 0 {
+    if number_of_digits.is_multiple_of(2) {
         let split = 10u64.pow(number_of_digits / 2);
         let split_value = x % split;
-        solve(x / split, steps - 1) + solve(split_value, steps - 1)
+        solve(x / split, steps - 1)?.checked_add(solve(split_value, steps - 1)?)
     } else {
-        solve(x * 2024, steps - 1)
+        solve(x.checked_mul(2024)?, steps - 1)
     }
 }
 
-fn solve_part(input: &[u64], steps: u64) -> u64 {
-    input.iter().map(|x| solve(*x, steps)).sum()
+fn solve_part(input: &[u64], steps: u64) -> Result<u64, AocError> {
+    input
+        .iter()
+        .map(|x| solve(*x, steps))
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| AocError::invalid("a stone no longer fits in an integer"))
+        .and_then(checked_sum)
 }
 
 #[aoc(day11, part1)]
-fn part1(input: &[u64]) -> u64 {
+pub(crate) fn part1(input: &[u64]) -> Result<u64, AocError> {
     solve_part(input, 25)
 }
 
 #[aoc(day11, part2)]
-fn part2(input: &[u64]) -> u64 {
+pub(crate) fn part2(input: &[u64]) -> Result<u64, AocError> {
     solve_part(input, 75)
 }
 
@@ -56,10 +60,21 @@ mod test_day11 {
     #[test]
     fn test_parse() {
         assert_eq!(parse_input(EXAMPLE).unwrap(), vec![125, 17]);
+        assert_eq!(parse_input("125  17\t0").unwrap(), vec![125, 17, 0]);
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), 55312);
+        assert_eq!(part1(&parse_input(EXAMPLE).unwrap()), Ok(55312));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        error::assert_no_panic(EXAMPLE, parse_input, &[&part1]);
+    }
+
+    #[test]
+    fn test_overflowing_stone() {
+        assert!(part1(&[u64::MAX / 2]).is_err());
     }
 }
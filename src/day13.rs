@@ -1,60 +1,53 @@
-use nom::{
-    branch::alt,
-    bytes::complete::tag,
-    character::complete,
-    multi::separated_list1,
-    sequence::{preceded, separated_pair},
-    IResult,
-};
+use nom::{bytes::complete::tag, character::complete::line_ending};
 
-struct ClawMachine {
-    a: (i64, i64),
-    b: (i64, i64),
-    prize: (i64, i64),
-}
+use crate::{
+    error::{self, checked_sum, AocError, ParseResult, Span},
+    parse::{axis_coordinates, blocks},
+    point::Vector,
+};
 
-fn parse_coords(input: &str) -> IResult<&str, (i64, i64)> {
-    let x = preceded(alt((tag("X="), tag("X+"))), complete::i64);
-    let y = preceded(alt((tag("Y="), tag("Y+"))), complete::i64);
-    separated_pair(x, tag(", "), y)(input)
+pub(crate) struct ClawMachine {
+    a: Vector,
+    b: Vector,
+    prize: Vector,
 }
 
-fn parse_claw_machine(input: &str) -> IResult<&str, ClawMachine> {
+fn parse_claw_machine(input: Span) -> ParseResult<ClawMachine> {
     let (input, _) = tag("Button A: ")(input)?;
-    let (input, a) = parse_coords(input)?;
-    let (input, _) = tag("\nButton B: ")(input)?;
-    let (input, b) = parse_coords(input)?;
-    let (input, _) = tag("\nPrize: ")(input)?;
-    let (input, prize) = parse_coords(input)?;
+    let (input, a) = axis_coordinates(input)?;
+    let (input, _) = line_ending(input)?;
+    let (input, _) = tag("Button B: ")(input)?;
+    let (input, b) = axis_coordinates(input)?;
+    let (input, _) = line_ending(input)?;
+    let (input, _) = tag("Prize: ")(input)?;
+    let (input, prize) = axis_coordinates(input)?;
 
     Ok((input, ClawMachine { a, b, prize }))
 }
 
-fn parse_claw_machines(input: &str) -> IResult<&str, Vec<ClawMachine>> {
-    separated_list1(tag("\n\n"), parse_claw_machine)(input)
+fn parse_claw_machines(input: Span) -> ParseResult<Vec<ClawMachine>> {
+    blocks(parse_claw_machine)(input)
 }
 
 #[aoc_generator(day13, part1)]
-fn input_generator(input: &str) -> Result<Vec<ClawMachine>, nom::Err<nom::error::Error<String>>> {
-    parse_claw_machines(input)
-        .map_err(crate::convert_error_to_owned)
-        .map(|(_, machines)| machines)
+pub(crate) fn input_generator(input: &str) -> Result<Vec<ClawMachine>, AocError> {
+    error::parse(input, "claw machines", parse_claw_machines)
 }
 
 #[aoc_generator(day13, part2)]
-fn input_generator_part2(
-    input: &str,
-) -> Result<Vec<ClawMachine>, nom::Err<nom::error::Error<String>>> {
-    let mut machines = parse_claw_machines(input)
-        .map_err(crate::convert_error_to_owned)
-        .map(|(_, machines)| machines)?;
-    machines.iter_mut().for_each(|machine| {
-        machine.prize = (
-            machine.prize.0 + 10000000000000,
-            machine.prize.1 + 10000000000000,
-        );
-    });
-    Ok(machines)
+pub(crate) fn input_generator_part2(input: &str) -> Result<Vec<ClawMachine>, AocError> {
+    let offset = 10000000000000;
+    error::parse(input, "claw machines", parse_claw_machines)?
+        .into_iter()
+        .map(|mut machine| {
+            machine.prize = Vector::new(
+                machine.prize.x.checked_add(offset)?,
+                machine.prize.y.checked_add(offset)?,
+            );
+            Some(machine)
+        })
+        .collect::<Option<_>>()
+        .ok_or_else(|| AocError::invalid("the prize is too far away"))
 }
 
 // a = press a
@@ -71,48 +64,64 @@ fn input_generator_part2(
 // a = (px * by - py * bx) / (ax * by - ay * bx)
 // f(b) =
 
-fn calqulate_tokens(claw_machine: &ClawMachine, part2: bool) -> Option<i64> {
-    let (ax, ay) = claw_machine.a;
-    let (bx, by) = claw_machine.b;
-    let (px, py) = claw_machine.prize;
+fn calqulate_tokens(claw_machine: &ClawMachine, part2: bool) -> Result<Option<i64>, AocError> {
+    let (ax, ay) = claw_machine.a.into();
+    let (bx, by) = claw_machine.b.into();
+    let (px, py) = claw_machine.prize.into();
+
+    let too_large = || AocError::invalid("the claw machine coordinates do not fit in an integer");
+    let determinant = |a: i64, b: i64, c: i64, d: i64| {
+        a.checked_mul(b)
+            .zip(c.checked_mul(d))
+            .and_then(|(ab, cd)| ab.checked_sub(cd))
+            .ok_or_else(too_large)
+    };
 
-    let a_nominator = px * by - py * bx;
-    let a_denominator = ax * by - ay * bx;
-    let b_nominator = px * ay - py * ax;
-    let b_denominator = bx * ay - by * ax;
+    let a_nominator = determinant(px, by, py, bx)?;
+    let a_denominator = determinant(ax, by, ay, bx)?;
+    let b_nominator = determinant(px, ay, py, ax)?;
+    let b_denominator = determinant(bx, ay, by, ax)?;
 
     if a_denominator == 0 || b_denominator == 0 {
-        return None;
+        return Ok(None);
     }
 
-    if a_nominator % a_denominator != 0 || b_nominator % b_denominator != 0 {
-        return None;
+    let (Some(a), Some(b)) = (
+        a_nominator.checked_div(a_denominator),
+        b_nominator.checked_div(b_denominator),
+    ) else {
+        return Err(too_large());
+    };
+    if a * a_denominator != a_nominator || b * b_denominator != b_nominator {
+        return Ok(None);
     }
 
-    let a = (px * by - py * bx) / (ax * by - ay * bx);
-    let b = (px * ay - py * ax) / (bx * ay - by * ax);
-
-    if !part2 && (a >= 100 || b >= 100) {
-        return None;
+    if a < 0 || b < 0 || (!part2 && (a >= 100 || b >= 100)) {
+        return Ok(None);
     }
 
-    Some(3 * a + b)
+    a.checked_mul(3)
+        .and_then(|a| a.checked_add(b))
+        .map(Some)
+        .ok_or_else(too_large)
 }
 
-#[aoc(day13, part1)]
-fn part1(machines: &[ClawMachine]) -> i64 {
-    machines
+fn total_tokens(machines: &[ClawMachine], part2: bool) -> Result<i64, AocError> {
+    let tokens = machines
         .iter()
-        .filter_map(|c| calqulate_tokens(c, false))
-        .sum()
+        .map(|c| calqulate_tokens(c, part2))
+        .collect::<Result<Vec<_>, _>>()?;
+    checked_sum(tokens.into_iter().flatten())
+}
+
+#[aoc(day13, part1)]
+pub(crate) fn part1(machines: &[ClawMachine]) -> Result<i64, AocError> {
+    total_tokens(machines, false)
 }
 
 #[aoc(day13, part2)]
-fn part2(machines: &[ClawMachine]) -> i64 {
-    machines
-        .iter()
-        .filter_map(|c| calqulate_tokens(c, true))
-        .sum()
+pub(crate) fn part2(machines: &[ClawMachine]) -> Result<i64, AocError> {
+    total_tokens(machines, true)
 }
 
 #[cfg(test)]
@@ -140,28 +149,37 @@ mod tests {
 
     #[test]
     fn test_parse() {
-        let (_, machines) = parse_claw_machines(TEST_INPUT).unwrap();
+        let machines = input_generator(TEST_INPUT).unwrap();
         assert_eq!(machines.len(), 4);
+
+        let crlf = TEST_INPUT.replace('\n', "\r\n");
+        assert_eq!(part1(&input_generator(&crlf).unwrap()), Ok(480));
     }
 
     #[test]
     fn test_coords() {
-        parse_coords("X=8400, Y=5400").unwrap();
-        parse_coords("X+8400, Y+5400").unwrap();
+        axis_coordinates(Span::new("X=8400, Y=5400")).unwrap();
+        axis_coordinates(Span::new("X+8400, Y+5400")).unwrap();
     }
 
     #[test]
     fn test_calqulate_tokes() {
         let machine = ClawMachine {
-            a: (94, 34),
-            b: (22, 67),
-            prize: (8400, 5400),
+            a: Vector::new(94, 34),
+            b: Vector::new(22, 67),
+            prize: Vector::new(8400, 5400),
         };
-        assert_eq!(calqulate_tokens(&machine, false), Some(280));
+        assert_eq!(calqulate_tokens(&machine, false), Ok(Some(280)));
     }
 
     #[test]
     fn test_part1() {
-        assert_eq!(part1(&input_generator(TEST_INPUT).unwrap()), 480);
+        assert_eq!(part1(&input_generator(TEST_INPUT).unwrap()), Ok(480));
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        error::assert_no_panic(TEST_INPUT, input_generator, &[&part1]);
+        error::assert_no_panic(TEST_INPUT, input_generator_part2, &[&part2]);
     }
 }
@@ -1,66 +1,71 @@
-use std::collections::{HashMap, HashSet};
-
-use nom::{
-    bytes::complete::tag,
-    character::complete::{self, newline},
-    multi::separated_list1,
-    sequence::{preceded, separated_pair, tuple},
-    IResult,
+use std::collections::HashMap;
+
+use nom::{character::complete::char, combinator::map_opt, sequence::separated_pair};
+
+use crate::{
+    error::{self, AocError, ParseResult, Span},
+    grid::{BitGrid, Topology},
+    parse::{labelled_coordinates, lines},
+    point::{Point, Vector},
 };
 
 #[derive(Debug, Clone, Copy)]
-struct Robot {
-    position: (i64, i64),
-    velocity: (i64, i64),
+pub(crate) struct Robot {
+    position: Point<usize>,
+    velocity: Vector,
 }
 
 impl Robot {
-    fn step(&self, grid_size: (i64, i64)) -> Robot {
-        let (x, y) = self.position;
-        let (vx, vy) = self.velocity;
-        Robot {
-            position: (
-                (x + vx).rem_euclid(grid_size.0),
-                (y + vy).rem_euclid(grid_size.1),
-            ),
+    /// One second later, an error if the robot is not on the `grid_size` area.
+    fn step(&self, grid_size: (usize, usize)) -> Result<Robot, AocError> {
+        let (width, height) = grid_size;
+        // Whole laps around the torus change nothing, and would only risk overflowing.
+        let velocity = Vector::new(
+            self.velocity.x.rem_euclid(width as i64),
+            self.velocity.y.rem_euclid(height as i64),
+        );
+        let position = Some(self.position)
+            .filter(|p| p.x < width && p.y < height)
+            .and_then(Point::to_signed)
+            .and_then(|p| Topology::TORUS.wrap(p + velocity, grid_size))
+            .ok_or_else(|| {
+                AocError::invalid(format!(
+                    "robot at {},{} is outside the {}x{} area",
+                    self.position.x, self.position.y, width, height
+                ))
+            })?
+            .into();
+        Ok(Robot {
+            position,
             velocity: self.velocity,
-        }
+        })
     }
 }
 
-fn parse_robot(input: &str) -> IResult<&str, Robot> {
-    let (input, (position, velocity)) = tuple((
-        preceded(
-            tag("p="),
-            separated_pair(complete::i64, tag(","), complete::i64),
-        ),
-        preceded(
-            tag(" v="),
-            separated_pair(complete::i64, tag(","), complete::i64),
-        ),
-    ))(input)?;
-    Ok((input, Robot { position, velocity }))
+fn parse_robot(input: Span) -> ParseResult<Robot> {
+    let position = map_opt(labelled_coordinates("p"), Vector::try_cast);
+    separated_pair(position, char(' '), labelled_coordinates("v"))(input)
+        .map(|(rest, (position, velocity))| (rest, Robot { position, velocity }))
 }
 
 #[aoc_generator(day14)]
-fn parse_input(input: &str) -> Result<Vec<Robot>, nom::Err<nom::error::Error<String>>> {
-    separated_list1(newline, parse_robot)(input)
-        .map_err(crate::convert_error_to_owned)
-        .map(|(_, v)| v)
+pub(crate) fn parse_input(input: &str) -> Result<Vec<Robot>, AocError> {
+    error::parse(input, "robots", lines(parse_robot))
 }
 
 #[aoc(day14, part1)]
-fn part1(robots: &[Robot]) -> usize {
+pub(crate) fn part1(robots: &[Robot]) -> Result<usize, AocError> {
     hundred_seconds(robots, (101, 103))
 }
 
-fn hundred_seconds(robots: &[Robot], grid_size: (i64, i64)) -> usize {
-    let mut quadrants = [0, 0, 0, 0];
+fn hundred_seconds(robots: &[Robot], grid_size: (usize, usize)) -> Result<usize, AocError> {
+    let mut quadrants = [0usize, 0, 0, 0];
     robots
         .iter()
         .map(|robot| robot_position_after_n_seconds(robot, grid_size, 100))
-        .for_each(|pos| {
-            let (x, y) = pos;
+        .try_for_each(|pos| {
+            let pos = pos?;
+            let Point { x, y } = pos;
             let quadrant = (x.cmp(&(grid_size.0 / 2)), y.cmp(&(grid_size.1 / 2)));
             match quadrant {
                 (std::cmp::Ordering::Less, std::cmp::Ordering::Less) => quadrants[0] += 1,
@@ -69,44 +74,28 @@ fn hundred_seconds(robots: &[Robot], grid_size: (i64, i64)) -> usize {
                 (std::cmp::Ordering::Greater, std::cmp::Ordering::Greater) => quadrants[3] += 1,
                 _ => {}
             }
-        });
-    quadrants.into_iter().product()
-}
-
-fn robot_position_after_n_seconds(robot: &Robot, grid_size: (i64, i64), n: usize) -> (i64, i64) {
-    let (x, y) = robot.position;
-    let (vx, vy) = robot.velocity;
-    let mut pos = (x, y);
-    (0..n).for_each(|_| {
-        pos = (
-            (pos.0 + vx).rem_euclid(grid_size.0),
-            (pos.1 + vy).rem_euclid(grid_size.1),
-        );
-    });
-    pos
-    // let (x, y) = robot.position;
-    // let (vx, vy) = robot.velocity;
-    // ((x + 100 * vx) % grid_size.0, (y + 100 * vy) % grid_size.1)
+            Ok::<_, AocError>(())
+        })?;
+    quadrants
+        .into_iter()
+        .try_fold(1usize, usize::checked_mul)
+        .ok_or_else(|| AocError::invalid("the answer does not fit in an integer"))
 }
 
-fn _print_tree(tree: &HashSet<(i64, i64)>, grid_size: (i64, i64)) {
-    for y in 0..grid_size.1 {
-        for x in 0..grid_size.0 {
-            if tree.contains(&(x, y)) {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        println!();
-    }
-    println!();
+fn robot_position_after_n_seconds(
+    robot: &Robot,
+    grid_size: (usize, usize),
+    n: usize,
+) -> Result<Point<usize>, AocError> {
+    (0..n)
+        .try_fold(*robot, |robot, _| robot.step(grid_size))
+        .map(|robot| robot.position)
 }
 
-fn is_candidate_tree(tree: &HashSet<(i64, i64)>, _grid_size: (i64, i64)) -> bool {
+fn is_candidate_tree(tree: &BitGrid) -> bool {
     // Check for consecutive positions along the y-axis (columns)
-    let mut column_map: HashMap<i64, Vec<i64>> = HashMap::new();
-    for &(x, y) in tree {
+    let mut column_map: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (x, y) in tree.ones() {
         column_map.entry(x).or_default().push(y);
     }
     for y_values in column_map.values() {
@@ -126,8 +115,8 @@ fn is_candidate_tree(tree: &HashSet<(i64, i64)>, _grid_size: (i64, i64)) -> bool
     }
 
     // Check for consecutive positions along the x-axis (rows)
-    let mut row_map: HashMap<i64, Vec<i64>> = HashMap::new();
-    for &(x, y) in tree {
+    let mut row_map: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (x, y) in tree.ones() {
         row_map.entry(y).or_default().push(x);
     }
     for x_values in row_map.values() {
@@ -149,25 +138,29 @@ fn is_candidate_tree(tree: &HashSet<(i64, i64)>, _grid_size: (i64, i64)) -> bool
     false
 }
 
-fn step_tree(robots: &[Robot], grid_size: (i64, i64)) -> Vec<Robot> {
+fn step_tree(robots: &[Robot], grid_size: (usize, usize)) -> Result<Vec<Robot>, AocError> {
     robots.iter().map(|robot| robot.step(grid_size)).collect()
 }
 
-fn find_christmas_tree(robots: &[Robot], grid_size: (i64, i64)) -> usize {
-    let mut step = 1;
-    let mut tree = step_tree(robots, grid_size);
-    loop {
-        let tree_set = HashSet::from_iter(tree.iter().map(|robot| robot.position));
-        if is_candidate_tree(&tree_set, grid_size) {
-            return step;
+/// The robots are back where they started after `width * height` seconds at the
+/// latest, so a tree that has not shown up by then never will.
+fn find_christmas_tree(robots: &[Robot], grid_size: (usize, usize)) -> Result<usize, AocError> {
+    let mut tree = robots.to_vec();
+    for step in 1..=grid_size.0 * grid_size.1 {
+        tree = step_tree(&tree, grid_size)?;
+        let mut tree_set = BitGrid::new(grid_size.0, grid_size.1);
+        tree.iter().for_each(|robot| {
+            tree_set.insert(robot.position);
+        });
+        if is_candidate_tree(&tree_set) {
+            return Ok(step);
         }
-        tree = step_tree(&tree, grid_size);
-        step += 1;
     }
+    Err(AocError::invalid("the robots never form a christmas tree"))
 }
 
 #[aoc(day14, part2)]
-fn part2(robots: &[Robot]) -> usize {
+pub(crate) fn part2(robots: &[Robot]) -> Result<usize, AocError> {
     find_christmas_tree(robots, (101, 103))
 }
 
@@ -195,14 +188,14 @@ mod tests {
         assert!(res.is_ok());
         let res = res.unwrap();
         assert_eq!(res.len(), 12);
-        assert_eq!(res[0].position, (0, 4));
-        assert_eq!(res[0].velocity, (3, -3));
+        assert_eq!(res[0].position, Point::new(0, 4));
+        assert_eq!(res[0].velocity, Vector::new(3, -3));
     }
 
     #[test]
     fn test_part1() {
         let res = parse_input(INPUT).unwrap();
-        assert_eq!(hundred_seconds(&res, (11, 7)), 12);
+        assert_eq!(hundred_seconds(&res, (11, 7)), Ok(12));
     }
 
     #[test]
@@ -216,6 +209,47 @@ mod tests {
                 robot_position_after_n_seconds(&robot, (11, 7), i)
             );
         }
-        assert_eq!(robot_position_after_n_seconds(&robot, (11, 7), 4), (10, 6));
+        assert_eq!(
+            robot_position_after_n_seconds(&robot, (11, 7), 4),
+            Ok(Point::new(10, 6))
+        );
+    }
+
+    #[test]
+    fn test_robot_outside_area() {
+        let robots = parse_input("p=11,3 v=1,1").unwrap();
+        assert_eq!(
+            hundred_seconds(&robots, (11, 7)).unwrap_err().to_string(),
+            "robot at 11,3 is outside the 11x7 area"
+        );
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let error = |input| parse_input(input).unwrap_err().to_string();
+        assert_eq!(
+            error("p=0,4 v=3,-3\np=6,3 v=\n"),
+            "line 2, column 9: expected a number\np=6,3 v=\n        ^"
+        );
+        assert_eq!(
+            error("p=0,4 v=3,-3\np=6,y v=1,1\n"),
+            "line 2, column 5: expected a number\np=6,y v=1,1\n    ^"
+        );
+        assert_eq!(
+            error("p=0,4 v=3,-3\np=6,3 v=99999999999999999999,1\n"),
+            "line 2, column 9: number too large\np=6,3 v=99999999999999999999,1\n        ^"
+        );
+    }
+
+    #[test]
+    fn test_malformed_input() {
+        error::assert_no_panic(
+            INPUT,
+            parse_input,
+            &[
+                &|robots: &[Robot]| hundred_seconds(robots, (11, 7)),
+                &|robots: &[Robot]| find_christmas_tree(robots, (11, 7)),
+            ],
+        );
     }
 }
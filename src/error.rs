@@ -0,0 +1,374 @@
+//! The error type shared by every day, with the location of bad input.
+
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+};
+
+use nom::{
+    character::complete::multispace0,
+    error::{ErrorKind, FromExternalError, ParseError},
+    IResult,
+};
+use nom_locate::LocatedSpan;
+use num::{CheckedAdd, Zero};
+
+use crate::grid::GridParseError;
+
+/// Puzzle input being parsed, which keeps track of its line and offset.
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+/// What a nom parser on a [`Span`] returns.
+pub type ParseResult<'a, O> = IResult<Span<'a>, O, SpanError<'a>>;
+
+/// Where a nom parser failed, with a message for the failures nom has no
+/// [`ErrorKind`] for, such as a number that is too large.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpanError<'a> {
+    pub input: Span<'a>,
+    pub kind: ErrorKind,
+    pub message: Option<String>,
+}
+
+impl<'a> SpanError<'a> {
+    /// A failure at the start of `input` that is explained by `message`.
+    pub fn new(input: Span<'a>, message: impl Into<String>) -> Self {
+        Self {
+            input,
+            kind: ErrorKind::Verify,
+            message: Some(message.into()),
+        }
+    }
+}
+
+impl<'a> ParseError<Span<'a>> for SpanError<'a> {
+    fn from_error_kind(input: Span<'a>, kind: ErrorKind) -> Self {
+        Self {
+            input,
+            kind,
+            message: None,
+        }
+    }
+
+    /// The innermost error says the most about what went wrong.
+    fn append(_: Span<'a>, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<'a, E> FromExternalError<Span<'a>, E> for SpanError<'a>
+where
+    E: Display,
+{
+    fn from_external_error(input: Span<'a>, kind: ErrorKind, error: E) -> Self {
+        Self {
+            input,
+            kind,
+            message: Some(error.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AocError {
+    /// The input could not be parsed, lines and columns are 1-based.
+    Parse {
+        line: usize,
+        column: usize,
+        /// The whole line holding the error, without its line ending.
+        source_line: String,
+        message: String,
+    },
+    /// The input parsed, but does not describe a puzzle that can be solved.
+    Invalid(String),
+}
+
+impl AocError {
+    /// A parse error at `line` and `column` of `input`.
+    pub fn at(input: &str, line: usize, column: usize, message: impl Into<String>) -> Self {
+        let source_line = input
+            .lines()
+            .nth(line.saturating_sub(1))
+            .unwrap_or_default()
+            .to_string();
+        AocError::Parse {
+            line,
+            column,
+            source_line,
+            message: message.into(),
+        }
+    }
+
+    /// A parse error where `rest`, a part of `input`, starts.
+    pub fn at_span(input: &str, rest: Span, message: impl Into<String>) -> Self {
+        Self::at(
+            input,
+            rest.location_line() as usize,
+            rest.get_utf8_column(),
+            message,
+        )
+    }
+
+    /// Convert the error of a nom parser that was run on all of `input`.
+    pub fn from_nom(input: &str, error: nom::Err<SpanError>) -> Self {
+        match error {
+            nom::Err::Incomplete(_) => {
+                let line = input.lines().count().max(1);
+                let column = input.lines().last().unwrap_or_default().chars().count() + 1;
+                Self::at(input, line, column, "unexpected end of input")
+            }
+            nom::Err::Error(e) | nom::Err::Failure(e) => {
+                let message = e.message.unwrap_or_else(|| describe(e.kind));
+                Self::at_span(input, e.input, message)
+            }
+        }
+    }
+
+    /// Convert the error of a [`Grid::parse`](crate::grid::Grid::parse) of `input`.
+    pub fn from_grid<E>(input: &str, error: GridParseError<E>) -> Self
+    where
+        E: Display,
+    {
+        let (line, column, message) = grid_error(error);
+        Self::at(input, line, column, message)
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        AocError::Invalid(message.into())
+    }
+}
+
+/// The line and column of a grid error, with what went wrong there.
+pub(crate) fn grid_error<E>(error: GridParseError<E>) -> (usize, usize, String)
+where
+    E: Display,
+{
+    match error {
+        GridParseError::RaggedLine {
+            line,
+            column,
+            expected,
+            found,
+        } => (
+            line,
+            column,
+            format!("expected {} characters, found {}", expected, found),
+        ),
+        GridParseError::InvalidCell {
+            line,
+            column,
+            found,
+            error,
+        } => (line, column, format!("{:?}: {}", found, error)),
+    }
+}
+
+/// What the nom parser that failed was looking for.
+fn describe(kind: ErrorKind) -> String {
+    match kind {
+        ErrorKind::Tag => "unexpected text".to_string(),
+        ErrorKind::Digit => "expected a number".to_string(),
+        ErrorKind::Char => "expected a specific character".to_string(),
+        ErrorKind::CrLf => "expected a line ending".to_string(),
+        ErrorKind::Space | ErrorKind::MultiSpace => "expected whitespace".to_string(),
+        ErrorKind::Eof => "unexpected end of input".to_string(),
+        ErrorKind::Verify | ErrorKind::MapOpt => "invalid value".to_string(),
+        kind => format!("unexpected input for {}", kind.description()),
+    }
+}
+
+impl Display for AocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AocError::Parse {
+                line,
+                column,
+                source_line,
+                message,
+            } => {
+                writeln!(f, "line {}, column {}: {}", line, column, message)?;
+                writeln!(f, "{}", source_line)?;
+                write!(f, "{:>1$}", "^", column)
+            }
+            AocError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for AocError {}
+
+/// Run `parser` over all of `input` and return what it parsed, with the location of any
+/// error. Only whitespace may follow what the parser consumed, `what` names the parser
+/// when it stops early.
+pub fn parse<'a, O>(
+    input: &'a str,
+    what: &str,
+    mut parser: impl FnMut(Span<'a>) -> ParseResult<'a, O>,
+) -> Result<O, AocError> {
+    let (rest, output) = parser(Span::new(input)).map_err(|e| AocError::from_nom(input, e))?;
+    let (rest, _) = multispace0::<_, SpanError>(rest).map_err(|e| AocError::from_nom(input, e))?;
+    if rest.is_empty() {
+        Ok(output)
+    } else {
+        Err(AocError::at_span(
+            input,
+            rest,
+            format!("unexpected input after {}", what),
+        ))
+    }
+}
+
+/// Sum `values`, failing instead of overflowing on inputs the puzzle never has.
+pub fn checked_sum<T>(values: impl IntoIterator<Item = T>) -> Result<T, AocError>
+where
+    T: CheckedAdd + Zero,
+{
+    values
+        .into_iter()
+        .try_fold(T::zero(), |sum, value| sum.checked_add(&value))
+        .ok_or_else(|| AocError::invalid("the answer does not fit in an integer"))
+}
+
+/// Broken variants of a valid puzzle input: every truncation, and every character
+/// removed, replaced or followed by something out of place.
+#[cfg(test)]
+pub(crate) fn malformed(valid: &str) -> Vec<String> {
+    let mut inputs = vec![String::new(), "\n".to_string(), "é".to_string()];
+    for (index, c) in valid.char_indices() {
+        let (before, after) = (&valid[..index], &valid[index + c.len_utf8()..]);
+        inputs.push(before.to_string());
+        inputs.push(format!("{}{}", before, after));
+        for replacement in ["x", "0", "9", "-", " ", "\n", "é", "99999999999999999999"] {
+            inputs.push(format!("{}{}{}", before, replacement, after));
+            inputs.push(format!("{}{}{}{}", before, c, replacement, after));
+        }
+    }
+    inputs
+}
+
+/// A part that solves what a generator made, for [`assert_no_panic`].
+#[cfg(test)]
+pub(crate) type Solver<'a, B, O> = &'a dyn Fn(&B) -> Result<O, AocError>;
+
+/// Run `solvers` on whatever `generator` makes of every [`malformed`] variant of `valid`,
+/// checking that nothing panics and that every error points into the broken input.
+#[cfg(test)]
+pub(crate) fn assert_no_panic<I, B, O>(
+    valid: &str,
+    generator: impl Fn(&str) -> Result<I, AocError>,
+    solvers: &[Solver<B, O>],
+) where
+    I: std::borrow::Borrow<B>,
+    B: ?Sized,
+{
+    for input in malformed(valid) {
+        let errors: Vec<AocError> = match generator(&input) {
+            Ok(generated) => solvers
+                .iter()
+                .filter_map(|solve| solve(generated.borrow()).err())
+                .collect(),
+            Err(error) => vec![error],
+        };
+        for error in errors {
+            match &error {
+                AocError::Parse {
+                    line,
+                    column,
+                    source_line,
+                    message,
+                } => {
+                    let lines: Vec<&str> = input.lines().collect();
+                    assert!(
+                        (1..=lines.len() + 1).contains(line),
+                        "{:?}: {}",
+                        input,
+                        error
+                    );
+                    assert_eq!(
+                        source_line,
+                        lines.get(line - 1).unwrap_or(&""),
+                        "{:?}: {}",
+                        input,
+                        error
+                    );
+                    assert!(
+                        (1..=source_line.chars().count() + 1).contains(column),
+                        "{:?}: {}",
+                        input,
+                        error
+                    );
+                    assert!(!message.is_empty(), "{:?}: {}", input, error);
+                }
+                AocError::Invalid(message) => {
+                    assert!(!message.is_empty(), "{:?}: {}", input, error)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+    use nom::{bytes::complete::tag, sequence::separated_pair};
+
+    use crate::parse::{lines, unsigned};
+
+    fn pairs(input: Span) -> ParseResult<Vec<(u64, u64)>> {
+        lines(separated_pair(unsigned, tag(","), unsigned))(input)
+    }
+
+    #[test]
+    fn test_parse_error_location() {
+        assert_eq!(parse("1,2\n3,4", "pairs", pairs), Ok(vec![(1, 2), (3, 4)]));
+        let error = parse("1,x\n", "pairs", pairs).unwrap_err();
+        assert_eq!(
+            error,
+            AocError::Parse {
+                line: 1,
+                column: 3,
+                source_line: "1,x".to_string(),
+                message: "expected a number".to_string(),
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            indoc! {"
+                line 1, column 3: expected a number
+                1,x
+                  ^"}
+        );
+    }
+
+    #[test]
+    fn test_unconsumed_input() {
+        assert_eq!(
+            parse("1,2\n3,4\n\n  \n", "pairs", pairs),
+            Ok(vec![(1, 2), (3, 4)])
+        );
+        assert_eq!(
+            parse("1,2\n3,4\n5,\n", "pairs", pairs)
+                .unwrap_err()
+                .to_string(),
+            "line 3, column 3: expected a number\n5,\n  ^"
+        );
+        assert_eq!(
+            parse("1,2\n\n3,4\n", "pairs", pairs)
+                .unwrap_err()
+                .to_string(),
+            "line 3, column 1: unexpected input after pairs\n3,4\n^"
+        );
+    }
+
+    #[test]
+    fn test_grid_error() {
+        let input = "abc\nab\n";
+        let error = crate::grid::Grid::parse_chars(input).unwrap_err();
+        assert_eq!(
+            AocError::from_grid(input, error).to_string(),
+            "line 2, column 3: expected 3 characters, found 2\nab\n  ^"
+        );
+        assert_eq!(AocError::invalid("no start").to_string(), "no start");
+    }
+}
@@ -0,0 +1,160 @@
+//! Lattice points on lines, rays and segments through two points, clipped to grid bounds.
+
+use num::Integer;
+
+use crate::point::Vector;
+
+/// Which part of the line through two points to walk.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Extent {
+    /// From the first point to the second, both included.
+    Segment,
+    /// From the first point through the second and on to the edge.
+    Ray,
+    /// From edge to edge in both directions.
+    Line,
+}
+
+/// A line through two distinct points, walked in fixed steps.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Line {
+    from: Vector,
+    step: Vector,
+    /// Number of steps from the first point to the second.
+    steps_between: i64,
+}
+
+impl Line {
+    /// Every lattice point on the line through `from` and `to`, the step between the two
+    /// is divided by the gcd of its coordinates. `None` if the points are equal.
+    pub fn through(from: impl Into<Vector>, to: impl Into<Vector>) -> Option<Self> {
+        let from = from.into();
+        let delta = to.into() - from;
+        let divisor = delta.x.gcd(&delta.y);
+        (divisor != 0).then(|| Self {
+            from,
+            step: Vector::new(delta.x / divisor, delta.y / divisor),
+            steps_between: divisor,
+        })
+    }
+
+    /// Only the points a whole multiple of the distance from `from` to `to` away.
+    /// `None` if the points are equal.
+    pub fn multiples(from: impl Into<Vector>, to: impl Into<Vector>) -> Option<Self> {
+        let from = from.into();
+        let step = to.into() - from;
+        (step != Vector::new(0, 0)).then_some(Self {
+            from,
+            step,
+            steps_between: 1,
+        })
+    }
+
+    pub fn step(&self) -> Vector {
+        self.step
+    }
+
+    /// Range of `k` for which `from + step * k` lies inside `bounds` on one axis.
+    fn axis_range(from: i64, step: i64, bound: usize) -> Option<(i64, i64)> {
+        let last = bound as i64 - 1;
+        match step {
+            0 => (0..=last).contains(&from).then_some((i64::MIN, i64::MAX)),
+            s if s > 0 => Some((
+                Integer::div_ceil(&-from, &s),
+                Integer::div_floor(&(last - from), &s),
+            )),
+            s => Some((
+                Integer::div_ceil(&(last - from), &s),
+                Integer::div_floor(&-from, &s),
+            )),
+        }
+    }
+
+    /// Points on `extent` of the line that lie within `bounds` (`(width, height)`), ordered
+    /// from the first point towards the second.
+    pub fn points(&self, extent: Extent, bounds: (usize, usize)) -> impl Iterator<Item = Vector> {
+        let x = Self::axis_range(self.from.x, self.step.x, bounds.0);
+        let y = Self::axis_range(self.from.y, self.step.y, bounds.1);
+        let (low, high) = match (x, y) {
+            (Some(x), Some(y)) => (x.0.max(y.0), x.1.min(y.1)),
+            _ => (1, 0),
+        };
+        let (low, high) = match extent {
+            Extent::Segment => (low.max(0), high.min(self.steps_between)),
+            Extent::Ray => (low.max(0), high),
+            Extent::Line => (low, high),
+        };
+        let (from, step) = (self.from, self.step);
+        (low..=high).map(move |k| from + step * k)
+    }
+
+    /// Like [`Line::points`], for grid positions.
+    pub fn positions(
+        &self,
+        extent: Extent,
+        bounds: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> {
+        self.points(extent, bounds)
+            .filter_map(|p| p.to_unsigned().map(Into::into))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_through_reduces_step() {
+        let line = Line::through((1, 1), (5, 3)).unwrap();
+        assert_eq!(line.step(), Vector::new(2, 1));
+        assert_eq!(
+            line.points(Extent::Segment, (10, 10)).collect_vec(),
+            [(1, 1), (3, 2), (5, 3)].map(Vector::from)
+        );
+        assert_eq!(
+            line.points(Extent::Ray, (10, 10)).collect_vec(),
+            [(1, 1), (3, 2), (5, 3), (7, 4), (9, 5)].map(Vector::from)
+        );
+        assert_eq!(
+            line.positions(Extent::Line, (8, 4)).collect_vec(),
+            [(1, 1), (3, 2), (5, 3)]
+        );
+        assert_eq!(
+            line.positions(Extent::Line, (8, 8)).collect_vec(),
+            [(1, 1), (3, 2), (5, 3), (7, 4)]
+        );
+    }
+
+    #[test]
+    fn test_multiples() {
+        let line = Line::multiples((4, 3), (5, 5)).unwrap();
+        assert_eq!(
+            line.points(Extent::Line, (10, 10)).collect_vec(),
+            [(3, 1), (4, 3), (5, 5), (6, 7), (7, 9)].map(Vector::from)
+        );
+        assert!(Line::multiples((1, 1), (1, 1)).is_none());
+        assert!(Line::through((1, 1), (1, 1)).is_none());
+    }
+
+    #[test]
+    fn test_axis_aligned_and_outside() {
+        let line = Line::through((0, 2), (3, 2)).unwrap();
+        assert_eq!(line.step(), Vector::new(1, 0));
+        assert_eq!(line.points(Extent::Line, (4, 4)).count(), 4);
+        assert_eq!(line.points(Extent::Line, (4, 2)).count(), 0);
+
+        let backwards = Line::through((3, 3), (2, 1)).unwrap();
+        assert_eq!(
+            backwards.positions(Extent::Line, (4, 4)).collect_vec(),
+            [(3, 3), (2, 1)]
+        );
+        assert_eq!(
+            Line::through((-2, -2), (-1, -1))
+                .unwrap()
+                .positions(Extent::Ray, (3, 3))
+                .collect_vec(),
+            [(0, 0), (1, 1), (2, 2)]
+        );
+    }
+}
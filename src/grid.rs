@@ -1,42 +1,245 @@
 use std::{
+    collections::HashMap,
+    convert::Infallible,
+    error::Error,
     fmt::{Debug, Display},
-    ops::{Index, IndexMut, Not},
+    ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Index, IndexMut, Not},
 };
 
 use itertools::Itertools;
 
-pub struct Grid<const N: usize, T> {
-    grid: [[T; N]; N],
+use crate::point::{Point, Vector};
+
+pub mod image;
+pub mod infinite;
+pub mod terminal;
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+    topology: Topology,
 }
 
-impl<const N: usize, T> Grid<N, T> {
-    pub fn iter(&self) -> GridIter<N, T> {
+impl<T> Grid<T> {
+    /// Create a grid from row-major `cells`, returns `None` if the number of cells does
+    /// not match `width * height`.
+    pub fn from_vec(width: usize, height: usize, cells: Vec<T>) -> Option<Self> {
+        (cells.len() == width * height).then_some(Self {
+            width,
+            height,
+            cells,
+            topology: Topology::BOUNDED,
+        })
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            cells: vec![value; width * height],
+            topology: Topology::BOUNDED,
+        }
+    }
+
+    pub fn new(width: usize, height: usize) -> Self
+    where
+        T: Default + Clone,
+    {
+        Self::filled(width, height, T::default())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// `(width, height)`, the bounds expected by [`Direction::step`].
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    /// Use `topology` for lookups, [`Grid::step`] and [`Grid::neighbours`].
+    pub fn with_topology(mut self, topology: Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn iter(&self) -> GridIter<'_, T> {
         GridIter {
             grid: self,
-            pos: (0, 0),
+            index: 0,
         }
     }
 
-    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
-        self.grid.get(pos.1)?.get(pos.0)
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        self.cells.chunks(self.width.max(1))
+    }
+
+    pub fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&T> {
+        self.cells.get(self.offset(pos.into())?)
+    }
+
+    pub fn get_mut(&mut self, pos: impl Into<(usize, usize)>) -> Option<&mut T> {
+        let offset = self.offset(pos.into())?;
+        self.cells.get_mut(offset)
+    }
+
+    /// Step one tile from `pos`, wrapping around the edges the grid's topology allows.
+    pub fn step<P>(&self, pos: P, direction: Direction) -> Option<P>
+    where
+        P: Into<(usize, usize)> + From<(usize, usize)>,
+    {
+        direction.step_with(pos, self.dimensions(), self.topology)
+    }
+
+    /// Positions and values reachable from `pos` with a single step in each of `directions`.
+    pub fn neighbours<'a, P>(
+        &'a self,
+        pos: P,
+        directions: impl IntoIterator<Item = Direction> + 'a,
+    ) -> impl Iterator<Item = (P, &'a T)> + 'a
+    where
+        P: Into<(usize, usize)> + From<(usize, usize)> + Copy + 'a,
+    {
+        directions.into_iter().filter_map(move |dir| {
+            let next = self.step(pos, dir)?;
+            Some((next, self.get(next)?))
+        })
+    }
+
+    fn offset(&self, pos: (usize, usize)) -> Option<usize> {
+        let x = self.topology.x.constrain(pos.0, self.width)?;
+        let y = self.topology.y.constrain(pos.1, self.height)?;
+        Some(y * self.width + x)
+    }
+
+    /// Parse a rectangular block of text, mapping every character with `cell`.
+    ///
+    /// Both `\n` and `\r\n` line endings are accepted and trailing newlines are ignored.
+    /// Every line must be as wide as the first one.
+    pub fn parse<E>(
+        input: &str,
+        mut cell: impl FnMut(char) -> Result<T, E>,
+    ) -> Result<Self, GridParseError<E>> {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::with_capacity(input.len());
+        for (line_nr, line) in input.trim_end_matches(['\r', '\n']).lines().enumerate() {
+            let mut line_width = 0;
+            for (column, c) in line.chars().enumerate() {
+                let value = cell(c).map_err(|error| GridParseError::InvalidCell {
+                    line: line_nr + 1,
+                    column: column + 1,
+                    found: c,
+                    error,
+                })?;
+                cells.push(value);
+                line_width += 1;
+            }
+            let expected = *width.get_or_insert(line_width);
+            if line_width != expected {
+                return Err(GridParseError::RaggedLine {
+                    line: line_nr + 1,
+                    column: expected.min(line_width) + 1,
+                    expected,
+                    found: line_width,
+                });
+            }
+            height += 1;
+        }
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height,
+            cells,
+            topology: Topology::BOUNDED,
+        })
+    }
+}
+
+impl Grid<char> {
+    /// Parse a rectangular block of text, keeping every character as is.
+    pub fn parse_chars(input: &str) -> Result<Self, GridParseError<Infallible>> {
+        Self::parse(input, Ok)
+    }
+}
+
+/// Error from [`Grid::parse`], lines and columns are 1-based.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridParseError<E> {
+    /// A line of a different width than the first, `column` is the first one past the
+    /// expected width, or where the line ends early.
+    RaggedLine {
+        line: usize,
+        column: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCell {
+        line: usize,
+        column: usize,
+        found: char,
+        error: E,
+    },
+}
+
+impl<E> Display for GridParseError<E>
+where
+    E: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GridParseError::RaggedLine {
+                line,
+                column,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {}, column {}: expected {} characters, found {}",
+                line, column, expected, found
+            ),
+            GridParseError::InvalidCell {
+                line,
+                column,
+                found,
+                error,
+            } => write!(
+                f,
+                "line {}, column {}: {:?}: {}",
+                line, column, found, error
+            ),
+        }
     }
 }
 
-impl<const N: usize, T> Display for Grid<N, T>
+impl<E> Error for GridParseError<E> where E: Debug + Display {}
+
+impl<T> Display for Grid<T>
 where
     T: Display,
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let grid = self
-            .grid
-            .iter()
+            .rows()
             .map(|line| line.iter().map(|tile| tile.to_string()).collect::<String>())
             .join("\n");
         writeln!(f, "\n{}", grid)
     }
 }
 
-impl<const N: usize, T> Debug for Grid<N, T>
+impl<T> Debug for Grid<T>
 where
     T: Display,
 {
@@ -45,47 +248,726 @@ where
     }
 }
 
-impl<const N: usize, T> Index<(usize, usize)> for Grid<N, T> {
+impl<T> Index<(usize, usize)> for Grid<T> {
     type Output = T;
 
     fn index(&self, index: (usize, usize)) -> &Self::Output {
-        self.grid.index(index.1).index(index.0)
+        match self.offset(index) {
+            Some(offset) => &self.cells[offset],
+            None => panic!(
+                "position {:?} out of bounds for grid of size {:?}",
+                index,
+                self.dimensions()
+            ),
+        }
+    }
+}
+
+impl<T> Index<Point<usize>> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, index: Point<usize>) -> &Self::Output {
+        self.index((index.x, index.y))
     }
 }
 
-impl<const N: usize, T> IndexMut<(usize, usize)> for Grid<N, T> {
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
     fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-        self.grid.index_mut(index.1).index_mut(index.0)
+        match self.offset(index) {
+            Some(offset) => &mut self.cells[offset],
+            None => panic!(
+                "position {:?} out of bounds for grid of size {:?}",
+                index,
+                self.dimensions()
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Point<usize>> for Grid<T> {
+    fn index_mut(&mut self, index: Point<usize>) -> &mut Self::Output {
+        self.index_mut((index.x, index.y))
+    }
+}
+
+pub struct GridIter<'a, T> {
+    grid: &'a Grid<T>,
+    index: usize,
+}
+
+impl<'a, T> Iterator for GridIter<'a, T> {
+    type Item = ((usize, usize), &'a T);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let tile = self.grid.cells.get(self.index)?;
+        let pos = (self.index % self.grid.width, self.index / self.grid.width);
+        self.index += 1;
+        Some((pos, tile))
+    }
+}
+
+/// Read access shared by dense and sparse grids, so algorithms only have to be written
+/// once.
+pub trait GridLike {
+    type Item;
+
+    /// `(width, height)`, the bounds expected by [`Direction::step`].
+    fn dimensions(&self) -> (usize, usize);
+
+    fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&Self::Item>;
+
+    /// Every position holding a value, in no particular order for sparse grids.
+    fn iter(&self) -> impl Iterator<Item = ((usize, usize), &Self::Item)>;
+
+    /// Whether `pos` holds a value.
+    fn contains(&self, pos: impl Into<(usize, usize)>) -> bool {
+        self.get(pos).is_some()
+    }
+
+    /// Whether `pos` is within `dimensions`, regardless of it holding a value. A wrapping
+    /// [`Topology`] is not applied, positions past the edge of a torus are `false` too.
+    fn in_storage(&self, pos: impl Into<(usize, usize)>) -> bool {
+        let (x, y) = pos.into();
+        let (width, height) = self.dimensions();
+        x < width && y < height
+    }
+}
+
+impl<T> GridLike for Grid<T> {
+    type Item = T;
+
+    fn dimensions(&self) -> (usize, usize) {
+        Grid::dimensions(self)
+    }
+
+    fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&T> {
+        Grid::get(self, pos)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        Grid::iter(self)
+    }
+}
+
+/// A bounded grid that only stores the positions holding a value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<T> {
+    width: usize,
+    height: usize,
+    cells: HashMap<(usize, usize), T>,
+}
+
+impl<T> SparseGrid<T> {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Store `value` at `pos`, returning the value it replaced.
+    ///
+    /// Panics if `pos` is outside the grid, like indexing a [`Grid`] does.
+    pub fn insert(&mut self, pos: impl Into<(usize, usize)>, value: T) -> Option<T> {
+        let pos = pos.into();
+        assert!(
+            self.in_storage(pos),
+            "position {:?} out of bounds for grid of size {:?}",
+            pos,
+            self.dimensions()
+        );
+        self.cells.insert(pos, value)
+    }
+
+    pub fn remove(&mut self, pos: impl Into<(usize, usize)>) -> Option<T> {
+        self.cells.remove(&pos.into())
+    }
+
+    pub fn get_mut(&mut self, pos: impl Into<(usize, usize)>) -> Option<&mut T> {
+        self.cells.get_mut(&pos.into())
+    }
+
+    /// Number of positions holding a value.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl<T> GridLike for SparseGrid<T> {
+    type Item = T;
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&T> {
+        self.cells.get(&pos.into())
+    }
+
+    fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
     }
 }
 
-impl<const N: usize, T> Default for Grid<N, T>
+impl<T> Grid<T> {
+    /// Copy the cells for which `keep` returns `true` into a [`SparseGrid`] of the same size.
+    pub fn to_sparse(&self, mut keep: impl FnMut(&T) -> bool) -> SparseGrid<T>
+    where
+        T: Clone,
+    {
+        let mut sparse = SparseGrid::new(self.width, self.height);
+        self.iter()
+            .filter(|(_, value)| keep(value))
+            .for_each(|(pos, value)| {
+                sparse.insert(pos, value.clone());
+            });
+        sparse
+    }
+}
+
+impl<T> Grid<T>
 where
-    T: Default + Copy,
+    T: Clone,
 {
-    fn default() -> Self {
+    /// Build a `width` by `height` grid where each position is copied from `source(pos)`.
+    fn remap(
+        &self,
+        width: usize,
+        height: usize,
+        topology: Topology,
+        source: impl Fn((usize, usize)) -> (usize, usize),
+    ) -> Self {
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|pos| self[source(pos)].clone())
+            .collect();
         Self {
-            grid: [[T::default(); N]; N],
+            width,
+            height,
+            cells,
+            topology,
+        }
+    }
+
+    fn swapped_topology(&self) -> Topology {
+        Topology {
+            x: self.topology.y,
+            y: self.topology.x,
         }
     }
+
+    /// Mirror along the main diagonal, `(x, y)` becomes `(y, x)`.
+    pub fn transpose(&self) -> Self {
+        self.remap(
+            self.height,
+            self.width,
+            self.swapped_topology(),
+            |(x, y)| (y, x),
+        )
+    }
+
+    /// Rotate a quarter turn clockwise.
+    pub fn rotate_90(&self) -> Self {
+        let height = self.height;
+        self.remap(height, self.width, self.swapped_topology(), |(x, y)| {
+            (y, height - 1 - x)
+        })
+    }
+
+    pub fn rotate_180(&self) -> Self {
+        let (width, height) = self.dimensions();
+        self.remap(width, height, self.topology, |(x, y)| {
+            (width - 1 - x, height - 1 - y)
+        })
+    }
+
+    /// Rotate a quarter turn counter-clockwise.
+    pub fn rotate_270(&self) -> Self {
+        let width = self.width;
+        self.remap(self.height, width, self.swapped_topology(), |(x, y)| {
+            (width - 1 - y, x)
+        })
+    }
+
+    /// Mirror left to right.
+    pub fn flip_horizontal(&self) -> Self {
+        let width = self.width;
+        self.remap(width, self.height, self.topology, |(x, y)| {
+            (width - 1 - x, y)
+        })
+    }
+
+    /// Mirror top to bottom.
+    pub fn flip_vertical(&self) -> Self {
+        let height = self.height;
+        self.remap(self.width, height, self.topology, |(x, y)| {
+            (x, height - 1 - y)
+        })
+    }
+}
+
+impl<T> Grid<T> {
+    /// Cells from `pos` onwards in `direction`, up to the edge of the grid.
+    ///
+    /// Rays always stop at the edge, even on a wrapping topology.
+    pub fn ray(&self, pos: impl Into<(usize, usize)>, direction: Direction) -> Ray<'_, T> {
+        let pos = pos.into();
+        Ray {
+            grid: self,
+            pos: self.in_storage(pos).then_some(pos),
+            direction,
+        }
+    }
+
+    /// Every maximal line running in `direction`, each starting at the edge the direction
+    /// points away from.
+    pub fn lines(&self, direction: Direction) -> impl Iterator<Item = Ray<'_, T>> {
+        let dimensions = self.dimensions();
+        (0..self.height)
+            .flat_map(move |y| (0..dimensions.0).map(move |x| (x, y)))
+            .filter(move |&pos| (!direction).step(pos, dimensions).is_none())
+            .map(move |pos| self.ray(pos, direction))
+    }
+
+    /// Every column, top to bottom.
+    pub fn columns(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        self.lines(South)
+    }
+
+    /// Every diagonal running from the top left towards the bottom right.
+    pub fn diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        self.lines(SouthEast)
+    }
+
+    /// Every diagonal running from the top right towards the bottom left.
+    pub fn anti_diagonals(&self) -> impl Iterator<Item = Ray<'_, T>> {
+        self.lines(SouthWest)
+    }
+
+    /// Borrow the `(width, height)` rectangle starting at `origin`, `None` if it does not
+    /// fit inside the grid.
+    pub fn view(
+        &self,
+        origin: (usize, usize),
+        dimensions: (usize, usize),
+    ) -> Option<SubGrid<'_, T>> {
+        let fits = origin.0.checked_add(dimensions.0)? <= self.width
+            && origin.1.checked_add(dimensions.1)? <= self.height;
+        fits.then_some(SubGrid {
+            grid: self,
+            origin,
+            width: dimensions.0,
+            height: dimensions.1,
+        })
+    }
 }
 
-pub struct GridIter<'a, const N: usize, T> {
-    grid: &'a Grid<N, T>,
-    pos: (usize, usize),
+/// Iterator over the cells on a straight line through a [`Grid`], see [`Grid::ray`].
+pub struct Ray<'a, T> {
+    grid: &'a Grid<T>,
+    pos: Option<(usize, usize)>,
+    direction: Direction,
 }
 
-impl<'a, const N: usize, T> Iterator for GridIter<'a, N, T> {
+impl<'a, T> Iterator for Ray<'a, T> {
     type Item = ((usize, usize), &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let pos = self.pos;
-        let tile = self.grid.get(pos)?;
-        self.pos = match self.pos {
-            (x, y) if x + 1 < N => (x + 1, y),
-            (_, y) => (0, y + 1),
-        };
-        Some((pos, tile))
+        let pos = self.pos?;
+        self.pos = self.direction.step(pos, self.grid.dimensions());
+        Some((pos, &self.grid[pos]))
+    }
+}
+
+/// A borrowed rectangle of a [`Grid`], positions are relative to its top left corner.
+pub struct SubGrid<'a, T> {
+    grid: &'a Grid<T>,
+    origin: (usize, usize),
+    width: usize,
+    height: usize,
+}
+
+impl<T> Clone for SubGrid<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for SubGrid<'_, T> {}
+
+impl<'a, T> SubGrid<'a, T> {
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    /// Position of the top left corner in the underlying grid.
+    pub fn origin(&self) -> (usize, usize) {
+        self.origin
+    }
+
+    pub fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&'a T> {
+        let (x, y) = pos.into();
+        if x < self.width && y < self.height {
+            self.grid.get((self.origin.0 + x, self.origin.1 + y))
+        } else {
+            None
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &'a T)> + 'a {
+        let view = *self;
+        (0..self.height)
+            .flat_map(move |y| (0..view.width).map(move |x| (x, y)))
+            .map(move |pos| {
+                (
+                    pos,
+                    &view.grid[(view.origin.0 + pos.0, view.origin.1 + pos.1)],
+                )
+            })
+    }
+
+    /// Copy the view into a grid of its own.
+    pub fn to_grid(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let cells = self.iter().map(|(_, value)| value.clone()).collect();
+        Grid {
+            width: self.width,
+            height: self.height,
+            cells,
+            topology: Topology::BOUNDED,
+        }
+    }
+}
+
+impl<T> GridLike for SubGrid<'_, T> {
+    type Item = T;
+
+    fn dimensions(&self) -> (usize, usize) {
+        SubGrid::dimensions(self)
+    }
+
+    fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&T> {
+        SubGrid::get(self, pos)
+    }
+
+    fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        SubGrid::iter(self)
+    }
+}
+
+impl<T> Index<(usize, usize)> for SubGrid<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        match self.get(index) {
+            Some(value) => value,
+            None => panic!(
+                "position {:?} out of bounds for view of size {:?}",
+                index,
+                self.dimensions()
+            ),
+        }
+    }
+}
+
+impl<T> Index<Point<usize>> for SubGrid<'_, T> {
+    type Output = T;
+
+    fn index(&self, index: Point<usize>) -> &Self::Output {
+        self.index((index.x, index.y))
+    }
+}
+
+/// A bounded grid of booleans packed 64 to a word, for visited sets and automata.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    width: usize,
+    height: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            words: vec![0; (width * height).div_ceil(64)],
+        }
+    }
+
+    fn bit(&self, pos: (usize, usize)) -> Option<(usize, u64)> {
+        let index =
+            (pos.0 < self.width && pos.1 < self.height).then_some(pos.1 * self.width + pos.0)?;
+        Some((index / 64, 1 << (index % 64)))
+    }
+
+    /// Whether `pos` is set, `false` outside the grid.
+    pub fn contains(&self, pos: impl Into<(usize, usize)>) -> bool {
+        self.bit(pos.into())
+            .is_some_and(|(word, mask)| self.words[word] & mask != 0)
+    }
+
+    /// Set `pos`, returns `true` if it was not set before.
+    ///
+    /// Panics if `pos` is outside the grid, like indexing a [`Grid`] does.
+    pub fn insert(&mut self, pos: impl Into<(usize, usize)>) -> bool {
+        let (word, mask) = self.bit_or_panic(pos.into());
+        let was_set = self.words[word] & mask != 0;
+        self.words[word] |= mask;
+        !was_set
+    }
+
+    /// Clear `pos`, returns `true` if it was set before.
+    pub fn remove(&mut self, pos: impl Into<(usize, usize)>) -> bool {
+        match self.bit(pos.into()) {
+            Some((word, mask)) => {
+                let was_set = self.words[word] & mask != 0;
+                self.words[word] &= !mask;
+                was_set
+            }
+            None => false,
+        }
+    }
+
+    fn bit_or_panic(&self, pos: (usize, usize)) -> (usize, u64) {
+        match self.bit(pos) {
+            Some(bit) => bit,
+            None => panic!(
+                "position {:?} out of bounds for grid of size {:?}",
+                pos,
+                (self.width, self.height)
+            ),
+        }
+    }
+
+    /// Number of set positions.
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    /// Every set position, in row-major order.
+    pub fn ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let width = self.width;
+        self.words.iter().enumerate().flat_map(move |(i, &word)| {
+            let mut remaining = word;
+            std::iter::from_fn(move || {
+                if remaining == 0 {
+                    return None;
+                }
+                let bit = remaining.trailing_zeros() as usize;
+                remaining &= remaining - 1;
+                let index = i * 64 + bit;
+                Some((index % width, index / width))
+            })
+        })
+    }
+
+    /// Move every set position one step in `direction`, positions stepping off the edge
+    /// are dropped.
+    pub fn shift(&self, direction: Direction) -> Self {
+        let (dx, dy) = direction.delta();
+        let mut shifted = self.shift_bits(dy * self.width as isize + dx);
+        // A horizontal step wraps bits from the end of one row into the start of the next,
+        // clear the column they landed in.
+        match dx {
+            1 => shifted.clear_column(0),
+            -1 => shifted.clear_column(self.width.saturating_sub(1)),
+            _ => {}
+        }
+        shifted
+    }
+
+    /// Move every bit `amount` indices up (or down when negative) in row-major order.
+    fn shift_bits(&self, amount: isize) -> Self {
+        let len = self.width * self.height;
+        let mut shifted = Self::new(self.width, self.height);
+        if amount.unsigned_abs() >= len {
+            return shifted;
+        }
+        let (word_shift, bit_shift) = (amount.unsigned_abs() / 64, amount.unsigned_abs() % 64);
+        let n = self.words.len();
+        for i in 0..n {
+            shifted.words[i] = if amount >= 0 {
+                let low = i.checked_sub(word_shift).map_or(0, |j| self.words[j]);
+                let carry = i
+                    .checked_sub(word_shift + 1)
+                    .filter(|_| bit_shift > 0)
+                    .map_or(0, |j| self.words[j] >> (64 - bit_shift));
+                (low << bit_shift) | carry
+            } else {
+                let high = self.words.get(i + word_shift).copied().unwrap_or(0);
+                let carry = self
+                    .words
+                    .get(i + word_shift + 1)
+                    .filter(|_| bit_shift > 0)
+                    .map_or(0, |w| w << (64 - bit_shift));
+                (high >> bit_shift) | carry
+            };
+        }
+        if !len.is_multiple_of(64) {
+            if let Some(last) = shifted.words.last_mut() {
+                *last &= (1 << (len % 64)) - 1;
+            }
+        }
+        shifted
+    }
+
+    fn clear_column(&mut self, x: usize) {
+        (0..self.height).for_each(|y| {
+            self.remove((x, y));
+        });
+    }
+
+    fn assert_same_size(&self, other: &Self) {
+        assert_eq!(
+            (self.width, self.height),
+            (other.width, other.height),
+            "bit grids must have the same size"
+        );
+    }
+}
+
+impl GridLike for BitGrid {
+    type Item = ();
+
+    fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&()> {
+        self.contains(pos).then_some(&())
+    }
+
+    fn iter(&self) -> impl Iterator<Item = ((usize, usize), &())> {
+        self.ones().map(|pos| (pos, &()))
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        self.assert_same_size(rhs);
+        self.words
+            .iter_mut()
+            .zip(&rhs.words)
+            .for_each(|(a, b)| *a |= b);
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, rhs: &BitGrid) {
+        self.assert_same_size(rhs);
+        self.words
+            .iter_mut()
+            .zip(&rhs.words)
+            .for_each(|(a, b)| *a &= b);
+    }
+}
+
+impl BitOr for &BitGrid {
+    type Output = BitGrid;
+
+    /// Union of both grids.
+    fn bitor(self, rhs: &BitGrid) -> BitGrid {
+        let mut union = self.clone();
+        union |= rhs;
+        union
+    }
+}
+
+impl BitAnd for &BitGrid {
+    type Output = BitGrid;
+
+    /// Intersection of both grids.
+    fn bitand(self, rhs: &BitGrid) -> BitGrid {
+        let mut intersection = self.clone();
+        intersection &= rhs;
+        intersection
+    }
+}
+
+impl<T> Grid<T> {
+    /// Set every position for which `set` returns `true`.
+    pub fn to_bits(&self, mut set: impl FnMut(&T) -> bool) -> BitGrid {
+        let mut bits = BitGrid::new(self.width, self.height);
+        self.iter()
+            .filter(|(_, value)| set(value))
+            .for_each(|(pos, _)| {
+                bits.insert(pos);
+            });
+        bits
+    }
+}
+
+/// How coordinates beyond one edge of a grid axis are treated.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Edge {
+    /// Stepping past the edge leaves the grid.
+    #[default]
+    Bounded,
+    /// Stepping past the edge comes back in on the opposite side.
+    Wrapping,
+}
+
+impl Edge {
+    fn constrain(self, coordinate: usize, bound: usize) -> Option<usize> {
+        match self {
+            Edge::Bounded => (coordinate < bound).then_some(coordinate),
+            Edge::Wrapping => (bound > 0).then(|| coordinate % bound),
+        }
+    }
+
+    fn constrain_signed(self, coordinate: i64, bound: usize) -> Option<usize> {
+        match self {
+            Edge::Bounded => usize::try_from(coordinate).ok().filter(|&c| c < bound),
+            Edge::Wrapping => (bound > 0).then(|| coordinate.rem_euclid(bound as i64) as usize),
+        }
+    }
+}
+
+/// Edge behaviour of both axes of a grid.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Topology {
+    pub x: Edge,
+    pub y: Edge,
+}
+
+impl Topology {
+    pub const BOUNDED: Topology = Topology {
+        x: Edge::Bounded,
+        y: Edge::Bounded,
+    };
+
+    pub const TORUS: Topology = Topology {
+        x: Edge::Wrapping,
+        y: Edge::Wrapping,
+    };
+
+    /// Bring a signed position inside `bounds`, `None` if it is off a bounded edge.
+    pub fn wrap(
+        &self,
+        pos: impl Into<(i64, i64)>,
+        bounds: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        let pos = pos.into();
+        Some((
+            self.x.constrain_signed(pos.0, bounds.0)?,
+            self.y.constrain_signed(pos.1, bounds.1)?,
+        ))
     }
 }
 
@@ -123,65 +1005,100 @@ impl Direction {
         }
     }
 
+    pub fn left_turn(&self) -> Direction {
+        self.turn(-2)
+    }
+
+    /// Turn clockwise by `eighths` multiples of 45°, negative values turn anticlockwise.
+    pub fn turn(&self, eighths: i32) -> Direction {
+        Self::all()[(*self as i32 + eighths).rem_euclid(8) as usize]
+    }
+
     pub fn cardinal() -> [Direction; 4] {
         [North, East, South, West]
     }
 
-    pub fn step(&self, pos: (usize, usize), bound: usize) -> Option<(usize, usize)> {
+    /// The offset of a single step in this direction.
+    pub fn vector(&self) -> Vector {
+        let (dx, dy) = self.delta();
+        Vector::new(dx as i64, dy as i64)
+    }
+
+    /// `(dx, dy)` of a single step, `y` grows south.
+    pub fn delta(&self) -> (isize, isize) {
         match self {
-            North => Some((pos.0, pos.1.checked_sub(1)?)),
-            South => {
-                let added = pos.1 + 1;
-                if added < bound {
-                    Some((pos.0, added))
-                } else {
-                    None
-                }
-            }
-            East => {
-                let added = pos.0 + 1;
-                if added < bound {
-                    Some((added, pos.1))
-                } else {
-                    None
-                }
-            }
-            West => Some((pos.0.checked_sub(1)?, pos.1)),
-            NorthEast => {
-                let added = pos.0 + 1;
-                if added < bound {
-                    Some((added, pos.1.checked_sub(1)?))
-                } else {
-                    None
-                }
-            }
-            SouthEast => {
-                let added = pos.0 + 1;
-                let added2 = pos.1 + 1;
-                if added < bound && added2 < bound {
-                    Some((added, added2))
-                } else {
-                    None
-                }
-            }
-            SouthWest => {
-                let added = pos.0.checked_sub(1)?;
-                let added2 = pos.1 + 1;
-                if added2 < bound {
-                    Some((added, added2))
-                } else {
-                    None
-                }
-            }
-            NorthWest => {
-                let added = pos.0.checked_sub(1)?;
-                if added < bound {
-                    Some((added, pos.1.checked_sub(1)?))
-                } else {
-                    None
-                }
-            }
+            North => (0, -1),
+            NorthEast => (1, -1),
+            East => (1, 0),
+            SouthEast => (1, 1),
+            South => (0, 1),
+            SouthWest => (-1, 1),
+            West => (-1, 0),
+            NorthWest => (-1, -1),
+        }
+    }
+
+    /// The direction of a single step, `None` unless both components are -1, 0 or 1 and
+    /// at least one is not 0.
+    pub fn from_delta(delta: (isize, isize)) -> Option<Direction> {
+        Self::all().into_iter().find(|d| d.delta() == delta)
+    }
+
+    /// The direction from `from` to `to` if they share a row, column or diagonal.
+    pub fn between<P>(from: P, to: P) -> Option<Direction>
+    where
+        P: Into<(usize, usize)>,
+    {
+        let (from, to) = (from.into(), to.into());
+        let (dx, dy) = (from.0.abs_diff(to.0), from.1.abs_diff(to.1));
+        if dx != 0 && dy != 0 && dx != dy {
+            return None;
         }
+        let sign = |a: usize, b: usize| b.cmp(&a) as isize;
+        Self::from_delta((sign(from.0, to.0), sign(from.1, to.1)))
+    }
+
+    /// Parse one of the arrows `^`, `>`, `v` and `<`.
+    pub fn from_arrow(c: char) -> Option<Direction> {
+        match c {
+            '^' => Some(North),
+            '>' => Some(East),
+            'v' => Some(South),
+            '<' => Some(West),
+            _ => None,
+        }
+    }
+
+    /// Parse an arrow, a compass letter `NESW` or a move `UDLR`.
+    pub fn from_char(c: char) -> Option<Direction> {
+        match c {
+            'N' | 'U' => Some(North),
+            'E' | 'R' => Some(East),
+            'S' | 'D' => Some(South),
+            'W' | 'L' => Some(West),
+            _ => Self::from_arrow(c),
+        }
+    }
+
+    /// Step one tile from `pos`, `bounds` is `(width, height)` and each axis is checked
+    /// against its own bound.
+    pub fn step<P>(&self, pos: P, bounds: (usize, usize)) -> Option<P>
+    where
+        P: Into<(usize, usize)> + From<(usize, usize)>,
+    {
+        self.step_with(pos, bounds, Topology::BOUNDED)
+    }
+
+    /// Like [`Direction::step`], but wrapping around the edges `topology` allows.
+    pub fn step_with<P>(&self, pos: P, bounds: (usize, usize), topology: Topology) -> Option<P>
+    where
+        P: Into<(usize, usize)> + From<(usize, usize)>,
+    {
+        let (x, y) = pos.into();
+        let (dx, dy) = self.delta();
+        topology
+            .wrap((x as i64 + dx as i64, y as i64 + dy as i64), bounds)
+            .map(P::from)
     }
 }
 
@@ -201,3 +1118,405 @@ impl Not for Direction {
         }
     }
 }
+
+impl TryFrom<char> for Direction {
+    type Error = char;
+
+    /// See [`Direction::from_char`].
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Self::from_char(c).ok_or(c)
+    }
+}
+
+/// A set of directions packed into a single byte.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct DirectionSet(u8);
+
+impl DirectionSet {
+    pub const EMPTY: DirectionSet = DirectionSet(0);
+    pub const ALL: DirectionSet = DirectionSet(u8::MAX);
+    pub const CARDINAL: DirectionSet = DirectionSet(0b0101_0101);
+
+    fn bit(direction: Direction) -> u8 {
+        1 << direction as u8
+    }
+
+    pub fn contains(&self, direction: Direction) -> bool {
+        self.0 & Self::bit(direction) != 0
+    }
+
+    /// Returns true if `direction` was not in the set yet.
+    pub fn insert(&mut self, direction: Direction) -> bool {
+        let added = !self.contains(direction);
+        self.0 |= Self::bit(direction);
+        added
+    }
+
+    /// Returns true if `direction` was in the set.
+    pub fn remove(&mut self, direction: Direction) -> bool {
+        let removed = self.contains(direction);
+        self.0 &= !Self::bit(direction);
+        removed
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// The directions in the set, clockwise from north.
+    pub fn iter(&self) -> impl Iterator<Item = Direction> {
+        let set = *self;
+        Direction::all()
+            .into_iter()
+            .filter(move |&d| set.contains(d))
+    }
+}
+
+impl FromIterator<Direction> for DirectionSet {
+    fn from_iter<I: IntoIterator<Item = Direction>>(iter: I) -> Self {
+        let mut set = Self::EMPTY;
+        iter.into_iter().for_each(|d| {
+            set.insert(d);
+        });
+        set
+    }
+}
+
+impl From<Direction> for DirectionSet {
+    fn from(direction: Direction) -> Self {
+        Self(Self::bit(direction))
+    }
+}
+
+impl BitOr for DirectionSet {
+    type Output = DirectionSet;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitAnd for DirectionSet {
+    type Output = DirectionSet;
+
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Self(self.0 & rhs.0)
+    }
+}
+
+impl Debug for DirectionSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rectangular_grid() {
+        let grid = Grid::from_vec(3, 2, vec![0, 1, 2, 3, 4, 5]).unwrap();
+        assert_eq!(grid.dimensions(), (3, 2));
+        assert_eq!(grid[(2, 0)], 2);
+        assert_eq!(grid[(0, 1)], 3);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.get((0, 2)), None);
+        assert_eq!(grid.iter().last(), Some(((2, 1), &5)));
+        assert_eq!(grid.to_string(), "\n012\n345\n");
+        assert!(Grid::from_vec(3, 2, vec![0; 5]).is_none());
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = Grid::parse("12\r\n34\r\n", |c| c.to_digit(10).ok_or("not a digit")).unwrap();
+        assert_eq!(grid.dimensions(), (2, 2));
+        assert_eq!(grid[(1, 1)], 4);
+
+        let err = Grid::parse("12\n3x\n", |c| c.to_digit(10).ok_or("not a digit")).unwrap_err();
+        assert_eq!(
+            err,
+            GridParseError::InvalidCell {
+                line: 2,
+                column: 2,
+                found: 'x',
+                error: "not a digit"
+            }
+        );
+        assert_eq!(err.to_string(), "line 2, column 2: 'x': not a digit");
+
+        let err = Grid::parse_chars("abc\nab\n").unwrap_err();
+        assert_eq!(
+            err,
+            GridParseError::RaggedLine {
+                line: 2,
+                column: 3,
+                expected: 3,
+                found: 2
+            }
+        );
+        let err = Grid::parse_chars(
+            "abc
+abcde
+",
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            GridParseError::RaggedLine {
+                line: 2,
+                column: 4,
+                expected: 3,
+                found: 5
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "line 2, column 4: expected 3 characters, found 5"
+        );
+    }
+
+    #[test]
+    fn test_torus() {
+        let grid = Grid::from_vec(3, 2, vec![0, 1, 2, 3, 4, 5])
+            .unwrap()
+            .with_topology(Topology::TORUS);
+        assert_eq!(grid[(3, 0)], 0);
+        assert_eq!(grid.get((4, 3)), Some(&4));
+        assert_eq!(grid.step((0, 0), NorthWest), Some((2, 1)));
+        assert_eq!(grid.iter().count(), 6);
+        let neighbours: Vec<_> = grid.neighbours((0, 0), Direction::cardinal()).collect();
+        assert_eq!(
+            neighbours,
+            vec![((0, 1), &3), ((1, 0), &1), ((0, 1), &3), ((2, 0), &2)]
+        );
+
+        let cylinder = Topology {
+            x: Edge::Wrapping,
+            y: Edge::Bounded,
+        };
+        assert_eq!(West.step_with((0, 1), (3, 2), cylinder), Some((2, 1)));
+        assert_eq!(South.step_with((0, 1), (3, 2), cylinder), None);
+        assert_eq!(cylinder.wrap((-7, 1), (3, 2)), Some((2, 1)));
+        assert_eq!(cylinder.wrap((0, -1), (3, 2)), None);
+    }
+
+    #[test]
+    fn test_points() {
+        let grid = Grid::from_vec(3, 2, vec![0, 1, 2, 3, 4, 5]).unwrap();
+        let pos = Point::new(1, 1);
+        assert_eq!(grid[pos], 4);
+        assert_eq!(grid.get(Point::new(2, 0)), Some(&2));
+        assert_eq!(North.step(pos, grid.dimensions()), Some(Point::new(1, 0)));
+        assert_eq!(
+            Vector::new(1, 1) + SouthWest.vector() * 2,
+            Vector::new(-1, 3)
+        );
+    }
+
+    #[test]
+    fn test_transformations() {
+        let grid = Grid::parse_chars("abc\ndef\n").unwrap();
+        let text = |g: &Grid<char>| g.to_string();
+        assert_eq!(text(&grid.transpose()), "\nad\nbe\ncf\n");
+        assert_eq!(text(&grid.rotate_90()), "\nda\neb\nfc\n");
+        assert_eq!(text(&grid.rotate_180()), "\nfed\ncba\n");
+        assert_eq!(text(&grid.rotate_270()), "\ncf\nbe\nad\n");
+        assert_eq!(text(&grid.flip_horizontal()), "\ncba\nfed\n");
+        assert_eq!(text(&grid.flip_vertical()), "\ndef\nabc\n");
+        assert_eq!(grid.rotate_90().rotate_270(), grid);
+        assert_eq!(grid.rotate_90().rotate_90(), grid.rotate_180());
+    }
+
+    #[test]
+    fn test_lines() {
+        let grid = Grid::parse_chars("abc\ndef\n").unwrap();
+        let text = |lines: Vec<Ray<'_, char>>| {
+            lines
+                .into_iter()
+                .map(|line| line.map(|(_, &c)| c).collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(text(grid.lines(East).collect()), ["abc", "def"]);
+        assert_eq!(text(grid.lines(West).collect()), ["cba", "fed"]);
+        assert_eq!(text(grid.columns().collect()), ["ad", "be", "cf"]);
+        assert_eq!(text(grid.diagonals().collect()), ["ae", "bf", "c", "d"]);
+        assert_eq!(
+            text(grid.anti_diagonals().collect()),
+            ["a", "bd", "ce", "f"]
+        );
+
+        let ray: Vec<_> = grid.ray((2, 1), NorthWest).collect();
+        assert_eq!(ray, [((2, 1), &'f'), ((1, 0), &'b')]);
+        assert_eq!(grid.ray((3, 0), West).count(), 0);
+    }
+
+    #[test]
+    fn test_bit_grid() {
+        let grid = Grid::parse_chars("#..\n.#.\n..#\n").unwrap();
+        let mut bits = grid.to_bits(|&c| c == '#');
+        assert_eq!(bits.count_ones(), 3);
+        assert!(bits.contains((1, 1)));
+        assert!(!bits.contains((1, 0)));
+        assert!(!bits.contains((5, 5)));
+        assert!(bits.insert(Point::new(2, 0)));
+        assert!(!bits.insert((2, 0)));
+        assert!(bits.remove((2, 0)));
+        assert_eq!(bits.ones().collect_vec(), [(0, 0), (1, 1), (2, 2)]);
+
+        let mut other = BitGrid::new(3, 3);
+        other.insert((1, 1));
+        other.insert((0, 1));
+        assert_eq!((&bits & &other).ones().collect_vec(), [(1, 1)]);
+        assert_eq!((&bits | &other).count_ones(), 4);
+    }
+
+    #[test]
+    fn test_bit_grid_shift() {
+        let grid = Grid::parse_chars("#..\n.#.\n..#\n").unwrap();
+        let bits = grid.to_bits(|&c| c == '#');
+        assert_eq!(bits.shift(East).ones().collect_vec(), [(1, 0), (2, 1)]);
+        assert_eq!(bits.shift(West).ones().collect_vec(), [(0, 1), (1, 2)]);
+        assert_eq!(bits.shift(North).ones().collect_vec(), [(1, 0), (2, 1)]);
+        assert_eq!(bits.shift(South).ones().collect_vec(), [(0, 1), (1, 2)]);
+        assert_eq!(bits.shift(SouthEast).ones().collect_vec(), [(1, 1), (2, 2)]);
+        assert_eq!(bits.shift(NorthEast).ones().collect_vec(), [(2, 0)]);
+
+        // Shifts across word boundaries.
+        let mut wide = BitGrid::new(100, 3);
+        wide.insert((63, 0));
+        wide.insert((99, 1));
+        wide.insert((0, 2));
+        assert_eq!(wide.shift(East).ones().collect_vec(), [(64, 0), (1, 2)]);
+        assert_eq!(wide.shift(West).ones().collect_vec(), [(62, 0), (98, 1)]);
+        assert_eq!(wide.shift(South).ones().collect_vec(), [(63, 1), (99, 2)]);
+        assert_eq!(wide.shift(North).ones().collect_vec(), [(99, 0), (0, 1)]);
+    }
+
+    #[test]
+    fn test_view() {
+        let grid = Grid::parse_chars("abcd\nefgh\nijkl\n").unwrap();
+        let view = grid.view((1, 1), (2, 2)).unwrap();
+        assert_eq!(view[(0, 0)], 'f');
+        assert_eq!(view[Point::new(1, 1)], 'k');
+        assert_eq!(view.get((2, 0)), None);
+        let cells: String = view.iter().map(|(_, &c)| c).collect();
+        assert_eq!(cells, "fgjk");
+        assert_eq!(view.to_grid().to_string(), "\nfg\njk\n");
+        assert!(grid.view((3, 0), (2, 1)).is_none());
+        assert!(grid.view((0, 0), (4, 3)).is_some());
+    }
+
+    fn count_occupied<G: GridLike>(grid: &G, positions: &[(usize, usize)]) -> usize {
+        positions.iter().filter(|&&pos| grid.contains(pos)).count()
+    }
+
+    #[test]
+    fn test_sparse_grid() {
+        let dense = Grid::parse_chars("#..\n.#.\n").unwrap();
+        let mut sparse = dense.to_sparse(|&c| c == '#');
+        assert_eq!(GridLike::dimensions(&sparse), (3, 2));
+        assert_eq!(sparse.len(), 2);
+        assert_eq!(sparse.get((1, 1)), Some(&'#'));
+        assert!(!sparse.contains((1, 0)));
+        assert!(sparse.in_storage((1, 0)));
+        assert!(!sparse.in_storage((3, 0)));
+
+        sparse.insert(Point::new(2, 1), '#');
+        assert_eq!(sparse.remove((0, 0)), Some('#'));
+        let mut positions: Vec<_> = GridLike::iter(&sparse).map(|(pos, _)| pos).collect();
+        positions.sort_unstable();
+        assert_eq!(positions, vec![(1, 1), (2, 1)]);
+
+        let probe = [(0, 0), (1, 1), (2, 1), (5, 5)];
+        assert_eq!(count_occupied(&dense, &probe), 3);
+        assert_eq!(count_occupied(&sparse, &probe), 2);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_sparse_insert_out_of_bounds() {
+        SparseGrid::new(2, 2).insert((2, 0), ());
+    }
+
+    #[test]
+    fn test_step_bounds() {
+        let bounds = (3, 2);
+        assert_eq!(East.step((1, 1), bounds), Some((2, 1)));
+        assert_eq!(East.step((2, 1), bounds), None);
+        assert_eq!(South.step((2, 0), bounds), Some((2, 1)));
+        assert_eq!(South.step((2, 1), bounds), None);
+        assert_eq!(NorthWest.step((0, 1), bounds), None);
+        assert_eq!(SouthWest.step((1, 0), bounds), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_direction_turns_and_deltas() {
+        assert_eq!(North.left_turn(), West);
+        assert_eq!(NorthEast.left_turn(), NorthWest);
+        assert_eq!(West.turn(3), NorthEast);
+        assert_eq!(East.turn(-1), NorthEast);
+        assert_eq!(South.turn(12), North);
+        assert!(Direction::all()
+            .iter()
+            .all(|d| d.left_turn().right_turn() == *d && d.turn(4) == !*d));
+        assert_eq!(SouthWest.delta(), (-1, 1));
+        assert_eq!(Direction::from_delta((-1, 1)), Some(SouthWest));
+        assert_eq!(Direction::from_delta((0, 0)), None);
+        assert_eq!(Direction::from_delta((2, 0)), None);
+    }
+
+    #[test]
+    fn test_direction_between_and_parsing() {
+        assert_eq!(Direction::between((1, 5), (1, 2)), Some(North));
+        assert_eq!(Direction::between((4, 0), (1, 3)), Some(SouthWest));
+        assert_eq!(Direction::between((0, 0), (1, 2)), None);
+        assert_eq!(Direction::between((2, 2), (2, 2)), None);
+        assert_eq!(
+            "^>v<".chars().map(Direction::from_arrow).collect_vec(),
+            [Some(North), Some(East), Some(South), Some(West)]
+        );
+        assert_eq!(
+            "NESWUDLRx".chars().map(Direction::from_char).collect_vec(),
+            [
+                Some(North),
+                Some(East),
+                Some(South),
+                Some(West),
+                Some(North),
+                Some(South),
+                Some(West),
+                Some(East),
+                None
+            ]
+        );
+        assert_eq!(Direction::try_from('x'), Err('x'));
+    }
+
+    #[test]
+    fn test_direction_set() {
+        let mut set: DirectionSet = [East, North, East].into_iter().collect();
+        assert_eq!(set.len(), 2);
+        assert_eq!(set.iter().collect_vec(), [North, East]);
+        assert!(set.insert(SouthWest));
+        assert!(!set.insert(SouthWest));
+        assert!(set.remove(North));
+        assert!(!set.contains(North));
+        assert_eq!(set & DirectionSet::CARDINAL, DirectionSet::from(East));
+        assert_eq!(
+            DirectionSet::CARDINAL.iter().collect_vec(),
+            Direction::cardinal()
+        );
+        assert_eq!(
+            DirectionSet::from(North) | DirectionSet::from(South),
+            [South, North].into_iter().collect()
+        );
+        assert_eq!(DirectionSet::ALL.len(), 8);
+        assert!(DirectionSet::EMPTY.is_empty());
+        assert_eq!(format!("{:?}", set), "{East, SouthWest}");
+    }
+}
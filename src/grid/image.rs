@@ -0,0 +1,363 @@
+//! Image export for grids and point sets as binary PPM/PGM or SVG, without any external
+//! tools or crates.
+
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use super::GridLike;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+    pub const GREEN: Rgb = Rgb(40, 180, 60);
+    pub const BLUE: Rgb = Rgb(50, 90, 220);
+    pub const YELLOW: Rgb = Rgb(240, 200, 40);
+
+    /// Perceived brightness, used for greyscale output.
+    pub fn luminance(&self) -> u8 {
+        ((299 * self.0 as u32 + 587 * self.1 as u32 + 114 * self.2 as u32) / 1000) as u8
+    }
+
+    fn hex(&self) -> String {
+        format!("#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageFormat {
+    /// Binary colour bitmap (`P6`).
+    Ppm,
+    /// Binary greyscale bitmap (`P5`).
+    Pgm,
+    Svg,
+}
+
+impl ImageFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Pgm => "pgm",
+            ImageFormat::Svg => "svg",
+        }
+    }
+
+    /// Pick the format from the extension of `path`.
+    pub fn from_path(path: impl AsRef<Path>) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "ppm" => Some(ImageFormat::Ppm),
+            "pgm" => Some(ImageFormat::Pgm),
+            "svg" => Some(ImageFormat::Svg),
+            _ => None,
+        }
+    }
+}
+
+/// A picture of a grid, one colour per cell, with optional paths and markers drawn on top.
+#[derive(Debug, Clone)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    cells: Vec<Rgb>,
+    paths: Vec<(Vec<(usize, usize)>, Rgb)>,
+    markers: Vec<((usize, usize), Rgb)>,
+    scale: usize,
+}
+
+impl Image {
+    /// Colour every position of `grid`, `colour` gets `None` for positions of a sparse grid
+    /// that hold no value.
+    pub fn new<G>(grid: &G, mut colour: impl FnMut(Option<&G::Item>) -> Rgb) -> Self
+    where
+        G: GridLike,
+    {
+        let (width, height) = grid.dimensions();
+        let cells = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|pos| colour(grid.get(pos)))
+            .collect();
+        Self {
+            width,
+            height,
+            cells,
+            paths: Vec::new(),
+            markers: Vec::new(),
+            scale: 1,
+        }
+    }
+
+    /// Pixels per cell side in bitmap output and user units per cell in SVG output.
+    pub fn scale(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    /// Draw a path through consecutive positions.
+    pub fn path<P>(mut self, path: impl IntoIterator<Item = P>, colour: Rgb) -> Self
+    where
+        P: Into<(usize, usize)>,
+    {
+        self.paths
+            .push((path.into_iter().map(Into::into).collect(), colour));
+        self
+    }
+
+    /// Mark a single position.
+    pub fn marker(mut self, pos: impl Into<(usize, usize)>, colour: Rgb) -> Self {
+        self.markers.push((pos.into(), colour));
+        self
+    }
+
+    /// Colour of every cell after drawing paths and markers.
+    fn raster(&self) -> Vec<Rgb> {
+        let mut cells = self.cells.clone();
+        let overlays = self
+            .paths
+            .iter()
+            .flat_map(|(path, colour)| path.iter().map(move |&pos| (pos, *colour)))
+            .chain(self.markers.iter().copied());
+        for ((x, y), colour) in overlays {
+            if x < self.width && y < self.height {
+                cells[y * self.width + x] = colour;
+            }
+        }
+        cells
+    }
+
+    /// Bitmap rows, each cell repeated `scale` times in both directions.
+    fn pixel_rows(&self) -> impl Iterator<Item = Vec<Rgb>> + '_ {
+        let raster = self.raster();
+        let scale = self.scale;
+        let width = self.width;
+        (0..self.height * scale).map(move |row| {
+            let y = row / scale;
+            raster[y * width..(y + 1) * width]
+                .iter()
+                .flat_map(|&colour| std::iter::repeat_n(colour, scale))
+                .collect()
+        })
+    }
+
+    pub fn write(&self, format: ImageFormat, out: &mut impl Write) -> io::Result<()> {
+        match format {
+            ImageFormat::Ppm => self.write_ppm(out),
+            ImageFormat::Pgm => self.write_pgm(out),
+            ImageFormat::Svg => self.write_svg(out),
+        }
+    }
+
+    pub fn write_ppm(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = (self.width * self.scale, self.height * self.scale);
+        write!(out, "P6\n{} {}\n255\n", width, height)?;
+        for row in self.pixel_rows() {
+            let bytes: Vec<u8> = row.iter().flat_map(|c| [c.0, c.1, c.2]).collect();
+            out.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    pub fn write_pgm(&self, out: &mut impl Write) -> io::Result<()> {
+        let (width, height) = (self.width * self.scale, self.height * self.scale);
+        write!(out, "P5\n{} {}\n255\n", width, height)?;
+        for row in self.pixel_rows() {
+            let bytes: Vec<u8> = row.iter().map(Rgb::luminance).collect();
+            out.write_all(&bytes)?;
+        }
+        Ok(())
+    }
+
+    /// Cells become squares, paths become polylines through cell centres and markers
+    /// become circles.
+    pub fn write_svg(&self, out: &mut impl Write) -> io::Result<()> {
+        let s = self.scale;
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" shape-rendering="crispEdges">"#,
+            self.width * s,
+            self.height * s
+        )?;
+        for (i, colour) in self.cells.iter().enumerate() {
+            let (x, y) = (i % self.width, i / self.width);
+            writeln!(
+                out,
+                r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"/>"#,
+                x * s,
+                y * s,
+                s,
+                s,
+                colour.hex()
+            )?;
+        }
+        let centre = |v: usize| v as f64 * s as f64 + s as f64 / 2.0;
+        for (path, colour) in &self.paths {
+            let points: Vec<String> = path
+                .iter()
+                .map(|&(x, y)| format!("{},{}", centre(x), centre(y)))
+                .collect();
+            writeln!(
+                out,
+                r#"<polyline points="{}" fill="none" stroke="{}" stroke-width="{}"/>"#,
+                points.join(" "),
+                colour.hex(),
+                s as f64 / 4.0
+            )?;
+        }
+        for &((x, y), colour) in &self.markers {
+            writeln!(
+                out,
+                r#"<circle cx="{}" cy="{}" r="{}" fill="{}"/>"#,
+                centre(x),
+                centre(y),
+                s as f64 / 3.0,
+                colour.hex()
+            )?;
+        }
+        writeln!(out, "</svg>")
+    }
+
+    /// Write to `path`, the format is taken from its extension.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let format = ImageFormat::from_path(&path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("unknown image extension: {}", path.as_ref().display()),
+            )
+        })?;
+        let mut out = BufWriter::new(File::create(path)?);
+        self.write(format, &mut out)?;
+        out.flush()
+    }
+}
+
+/// Writes numbered images, `frame_00000.ppm`, `frame_00001.ppm` and so on, for stepping
+/// through a simulation.
+#[derive(Debug)]
+pub struct FrameWriter {
+    directory: PathBuf,
+    prefix: String,
+    format: ImageFormat,
+    next: usize,
+}
+
+impl FrameWriter {
+    /// Creates `directory` if it does not exist yet.
+    pub fn new(
+        directory: impl Into<PathBuf>,
+        prefix: impl Into<String>,
+        format: ImageFormat,
+    ) -> io::Result<Self> {
+        let directory = directory.into();
+        fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            prefix: prefix.into(),
+            format,
+            next: 0,
+        })
+    }
+
+    /// Write the next frame, returning the path it was written to.
+    pub fn write(&mut self, image: &Image) -> io::Result<PathBuf> {
+        let path = self.directory.join(format!(
+            "{}_{:05}.{}",
+            self.prefix,
+            self.next,
+            self.format.extension()
+        ));
+        let mut out = BufWriter::new(File::create(&path)?);
+        image.write(self.format, &mut out)?;
+        out.flush()?;
+        self.next += 1;
+        Ok(path)
+    }
+
+    /// Number of frames written so far.
+    pub fn frames(&self) -> usize {
+        self.next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{BitGrid, Grid};
+
+    fn image() -> Image {
+        let grid = Grid::parse_chars("#.\n..\n").unwrap();
+        Image::new(&grid, |c| match c {
+            Some('#') => Rgb::WHITE,
+            _ => Rgb::BLACK,
+        })
+    }
+
+    #[test]
+    fn test_ppm() {
+        let mut out = Vec::new();
+        image()
+            .marker((1, 1), Rgb::RED)
+            .write_ppm(&mut out)
+            .unwrap();
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(
+            &out[header.len()..],
+            &[255, 255, 255, 0, 0, 0, 0, 0, 0, 220, 40, 40]
+        );
+    }
+
+    #[test]
+    fn test_pgm_scaled() {
+        let mut out = Vec::new();
+        image()
+            .path([(0, 1), (1, 1)], Rgb::WHITE)
+            .scale(2)
+            .write_pgm(&mut out)
+            .unwrap();
+        let header = b"P5\n4 4\n255\n";
+        assert_eq!(&out[..header.len()], header);
+        assert_eq!(
+            &out[header.len()..],
+            &[255, 255, 0, 0, 255, 255, 0, 0, 255, 255, 255, 255, 255, 255, 255, 255]
+        );
+    }
+
+    #[test]
+    fn test_svg_sparse() {
+        let mut bits = BitGrid::new(3, 1);
+        bits.insert((1, 0));
+        let mut out = Vec::new();
+        Image::new(&bits, |v| if v.is_some() { Rgb::GREEN } else { Rgb::WHITE })
+            .scale(10)
+            .path([(0, 0), (2, 0)], Rgb::BLUE)
+            .marker((2, 0), Rgb::RED)
+            .write_svg(&mut out)
+            .unwrap();
+        let svg = String::from_utf8(out).unwrap();
+        assert!(
+            svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="10""#)
+        );
+        assert!(svg.contains(r##"<rect x="10" y="0" width="10" height="10" fill="#28b43c"/>"##));
+        assert!(svg.contains(r#"<polyline points="5,5 25,5""#));
+        assert!(svg.contains(r#"<circle cx="25" cy="5""#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn test_frames() {
+        let directory = std::env::temp_dir().join(format!("aoc2024-frames-{}", std::process::id()));
+        let mut frames = FrameWriter::new(&directory, "walk", ImageFormat::Pgm).unwrap();
+        let first = frames.write(&image()).unwrap();
+        let second = frames.write(&image()).unwrap();
+        assert_eq!(first.file_name().unwrap(), "walk_00000.pgm");
+        assert_eq!(second.file_name().unwrap(), "walk_00001.pgm");
+        assert_eq!(frames.frames(), 2);
+        assert!(fs::read(&second).unwrap().starts_with(b"P5\n2 2\n"));
+        fs::remove_dir_all(directory).unwrap();
+    }
+}
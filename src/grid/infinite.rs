@@ -0,0 +1,286 @@
+//! A grid without fixed bounds, addressed by signed coordinates.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Display,
+};
+
+use super::{Direction, GridLike};
+use crate::point::Vector;
+
+/// A grid that grows to fit every write, so walks and spreading patterns can go in any
+/// direction without knowing their extent up front.
+///
+/// Cells are stored by position, so memory follows the number of occupied cells rather
+/// than the area they are spread over.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InfiniteGrid<T> {
+    cells: HashMap<Vector, T>,
+    /// Occupied cells per row and per column, the first and last of each are the
+    /// bounding box.
+    rows: BTreeMap<i64, usize>,
+    columns: BTreeMap<i64, usize>,
+}
+
+impl<T> Default for InfiniteGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> InfiniteGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+            rows: BTreeMap::new(),
+            columns: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, pos: impl Into<Vector>) -> Option<&T> {
+        self.cells.get(&pos.into())
+    }
+
+    pub fn get_mut(&mut self, pos: impl Into<Vector>) -> Option<&mut T> {
+        self.cells.get_mut(&pos.into())
+    }
+
+    pub fn contains(&self, pos: impl Into<Vector>) -> bool {
+        self.cells.contains_key(&pos.into())
+    }
+
+    /// Store `value` at `pos` and return the value it replaced.
+    pub fn insert(&mut self, pos: impl Into<Vector>, value: T) -> Option<T> {
+        let pos = pos.into();
+        let previous = self.cells.insert(pos, value);
+        if previous.is_none() {
+            *self.rows.entry(pos.y).or_default() += 1;
+            *self.columns.entry(pos.x).or_default() += 1;
+        }
+        previous
+    }
+
+    pub fn remove(&mut self, pos: impl Into<Vector>) -> Option<T> {
+        let pos = pos.into();
+        let removed = self.cells.remove(&pos)?;
+        uncount(&mut self.rows, pos.y);
+        uncount(&mut self.columns, pos.x);
+        Some(removed)
+    }
+
+    /// Number of occupied cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Inclusive `(min, max)` corners of the occupied cells, `None` while the grid is empty.
+    pub fn bounding_box(&self) -> Option<(Vector, Vector)> {
+        let (&min_x, _) = self.columns.first_key_value()?;
+        let (&max_x, _) = self.columns.last_key_value()?;
+        let (&min_y, _) = self.rows.first_key_value()?;
+        let (&max_y, _) = self.rows.last_key_value()?;
+        Some((Vector::new(min_x, min_y), Vector::new(max_x, max_y)))
+    }
+
+    /// Every occupied cell with its position, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Vector, &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    /// The position one step away, which always exists on an unbounded grid.
+    pub fn step(&self, pos: impl Into<Vector>, direction: Direction) -> Vector {
+        pos.into() + direction.vector()
+    }
+
+    /// The occupied region as a bounded [`GridLike`], for rendering with
+    /// [`Highlighted`](super::terminal::Highlighted) or [`Image`](super::image::Image).
+    pub fn occupied(&self) -> Occupied<'_, T> {
+        Occupied { grid: self }
+    }
+}
+
+/// Take one cell off the count of `line`, forgetting lines that become empty.
+fn uncount(counts: &mut BTreeMap<i64, usize>, line: i64) {
+    if let Some(count) = counts.get_mut(&line) {
+        *count -= 1;
+        if *count == 0 {
+            counts.remove(&line);
+        }
+    }
+}
+
+/// The bounding box of an [`InfiniteGrid`], with `(0, 0)` at its top left corner.
+///
+/// Displays empty cells as `.`.
+pub struct Occupied<'a, T> {
+    grid: &'a InfiniteGrid<T>,
+}
+
+impl<T> Occupied<'_, T> {
+    /// Position in the infinite grid of `(0, 0)` in this view.
+    pub fn origin(&self) -> Vector {
+        self.grid
+            .bounding_box()
+            .map_or(Vector::new(0, 0), |(min, _)| min)
+    }
+
+    /// Position in the infinite grid of a position in this view.
+    pub fn to_infinite(&self, pos: impl Into<(usize, usize)>) -> Vector {
+        let (x, y) = pos.into();
+        let origin = self.origin();
+        // Wrapping, because a view spanning more than `i64::MAX` has offsets that do not
+        // fit in an `i64`.
+        Vector::new(
+            origin.x.wrapping_add(x as i64),
+            origin.y.wrapping_add(y as i64),
+        )
+    }
+}
+
+impl<T> GridLike for Occupied<'_, T> {
+    type Item = T;
+
+    fn dimensions(&self) -> (usize, usize) {
+        self.grid.bounding_box().map_or((0, 0), |(min, max)| {
+            (
+                (max.x.abs_diff(min.x) as usize).saturating_add(1),
+                (max.y.abs_diff(min.y) as usize).saturating_add(1),
+            )
+        })
+    }
+
+    fn get(&self, pos: impl Into<(usize, usize)>) -> Option<&T> {
+        let pos = pos.into();
+        self.in_storage(pos)
+            .then(|| self.grid.get(self.to_infinite(pos)))
+            .flatten()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        let origin = self.origin();
+        self.grid.iter().map(move |(pos, value)| {
+            let offset = (pos.x.abs_diff(origin.x), pos.y.abs_diff(origin.y));
+            ((offset.0 as usize, offset.1 as usize), value)
+        })
+    }
+}
+
+impl<T> Display for Occupied<'_, T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (width, height) = self.dimensions();
+        for y in 0..height {
+            for x in 0..width {
+                match self.get((x, y)) {
+                    Some(value) => write!(f, "{}", value)?,
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_grows_in_every_direction() {
+        let mut grid = InfiniteGrid::new();
+        assert_eq!(grid.bounding_box(), None);
+        assert_eq!(grid.insert((0, 0), 'a'), None);
+        assert_eq!(grid.insert((-3, 2), 'b'), None);
+        assert_eq!(grid.insert((5, -4), 'c'), None);
+        assert_eq!(grid.insert((0, 0), 'd'), Some('a'));
+        assert_eq!(grid.len(), 3);
+        assert_eq!(grid.get((-3, 2)), Some(&'b'));
+        assert_eq!(grid.get((5, -4)), Some(&'c'));
+        assert_eq!(grid.get((1, 1)), None);
+        assert_eq!(grid.get((100, 100)), None);
+        assert_eq!(
+            grid.bounding_box(),
+            Some((Vector::new(-3, -4), Vector::new(5, 2)))
+        );
+        assert_eq!(
+            grid.iter()
+                .map(|(pos, &c)| (pos.into(), c))
+                .sorted()
+                .collect_vec(),
+            [((-3, 2), 'b'), ((0, 0), 'd'), ((5, -4), 'c')]
+        );
+
+        assert_eq!(grid.remove((5, -4)), Some('c'));
+        assert_eq!(grid.remove((5, -4)), None);
+        assert_eq!(
+            grid.bounding_box(),
+            Some((Vector::new(-3, 0), Vector::new(0, 2)))
+        );
+    }
+
+    #[test]
+    fn test_far_apart_cells() {
+        let mut grid = InfiniteGrid::new();
+        let far = Vector::new(1_000_000_000_000, -1_000_000_000_000);
+        grid.insert((0, 0), 0);
+        grid.insert(far, 1);
+        grid.insert((i64::MIN, i64::MAX), 2);
+        assert_eq!(grid.get(far), Some(&1));
+        let occupied = grid.occupied();
+        assert_eq!(occupied.iter().count(), 3);
+        assert!(occupied
+            .iter()
+            .all(|(pos, &v)| occupied.get(pos) == Some(&v)));
+        assert_eq!(
+            grid.bounding_box(),
+            Some((
+                Vector::new(i64::MIN, -1_000_000_000_000),
+                Vector::new(far.x, i64::MAX)
+            ))
+        );
+
+        // Clearing a shape edge by edge keeps the box tight.
+        let mut grid = InfiniteGrid::new();
+        for x in 0..10 {
+            for y in 0..10 {
+                grid.insert((x, y), ());
+            }
+        }
+        for x in 0..9 {
+            (0..10).for_each(|y| assert_eq!(grid.remove((x, y)), Some(())));
+            assert_eq!(
+                grid.bounding_box(),
+                Some((Vector::new(x + 1, 0), Vector::new(9, 9)))
+            );
+        }
+        (0..10).for_each(|y| {
+            grid.remove((9, y));
+        });
+        assert!(grid.is_empty());
+        assert_eq!(grid.bounding_box(), None);
+    }
+
+    #[test]
+    fn test_walk_and_render() {
+        let mut grid = InfiniteGrid::new();
+        let mut pos = Vector::new(0, 0);
+        for direction in [Direction::North, Direction::West, Direction::West] {
+            grid.insert(pos, '#');
+            pos = grid.step(pos, direction);
+        }
+        grid.insert(pos, '@');
+        let occupied = grid.occupied();
+        assert_eq!(occupied.origin(), Vector::new(-2, -1));
+        assert_eq!(occupied.dimensions(), (3, 2));
+        assert_eq!(occupied.get((0, 0)), Some(&'@'));
+        assert_eq!(occupied.to_string(), "@##\n..#\n");
+    }
+}
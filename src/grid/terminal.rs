@@ -0,0 +1,156 @@
+//! Terminal rendering of grids with highlighted positions drawn in ANSI colours.
+
+use std::{
+    collections::HashSet,
+    env,
+    fmt::{self, Display},
+    io::{self, IsTerminal},
+};
+
+use super::GridLike;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Colour {
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Colour {
+    fn code(&self) -> u8 {
+        match self {
+            Colour::Red => 31,
+            Colour::Green => 32,
+            Colour::Yellow => 33,
+            Colour::Blue => 34,
+            Colour::Magenta => 35,
+            Colour::Cyan => 36,
+        }
+    }
+}
+
+/// Whether stdout should get colour codes: it has to be a terminal and `NO_COLOR` must
+/// not be set.
+pub fn colour_supported() -> bool {
+    io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none()
+}
+
+type CellText<'a, T> = Box<dyn Fn(Option<&T>) -> String + 'a>;
+
+/// A grid ready to be printed, with sets of positions such as a trail or the matches of a
+/// search drawn in colour.
+pub struct Highlighted<'a, G>
+where
+    G: GridLike,
+{
+    grid: &'a G,
+    text: CellText<'a, G::Item>,
+    layers: Vec<(HashSet<(usize, usize)>, Colour)>,
+    colour: bool,
+}
+
+impl<'a, G> Highlighted<'a, G>
+where
+    G: GridLike,
+    G::Item: Display,
+{
+    /// Print every cell with its `Display` implementation, empty positions of a sparse
+    /// grid as `.`.
+    pub fn new(grid: &'a G) -> Self {
+        Self::with_cells(grid, |value| {
+            value.map_or_else(|| ".".to_string(), ToString::to_string)
+        })
+    }
+}
+
+impl<'a, G> Highlighted<'a, G>
+where
+    G: GridLike,
+{
+    /// Print every cell as `text` returns it, `None` for empty positions of a sparse grid.
+    pub fn with_cells(grid: &'a G, text: impl Fn(Option<&G::Item>) -> String + 'a) -> Self {
+        Self {
+            grid,
+            text: Box::new(text),
+            layers: Vec::new(),
+            colour: colour_supported(),
+        }
+    }
+
+    /// Draw `positions` in `colour`, later highlights are drawn over earlier ones.
+    pub fn highlight<P>(mut self, positions: impl IntoIterator<Item = P>, colour: Colour) -> Self
+    where
+        P: Into<(usize, usize)>,
+    {
+        self.layers
+            .push((positions.into_iter().map(Into::into).collect(), colour));
+        self
+    }
+
+    /// Force colour output on or off instead of detecting a terminal.
+    pub fn colour(mut self, enabled: bool) -> Self {
+        self.colour = enabled;
+        self
+    }
+}
+
+impl<G> Display for Highlighted<'_, G>
+where
+    G: GridLike,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (width, height) = self.grid.dimensions();
+        for y in 0..height {
+            for x in 0..width {
+                let text = (self.text)(self.grid.get((x, y)));
+                let colour = self
+                    .layers
+                    .iter()
+                    .rev()
+                    .find(|(positions, _)| positions.contains(&(x, y)))
+                    .map(|(_, colour)| colour);
+                match colour {
+                    Some(colour) if self.colour => {
+                        write!(f, "\x1b[1;{}m{}\x1b[0m", colour.code(), text)?
+                    }
+                    _ => write!(f, "{}", text)?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::grid::{BitGrid, Grid};
+
+    #[test]
+    fn test_highlight() {
+        let grid = Grid::parse_chars("ab\ncd\n").unwrap();
+        let view = Highlighted::new(&grid)
+            .highlight([(0, 0), (1, 1)], Colour::Red)
+            .highlight([(1, 1)], Colour::Green)
+            .colour(true);
+        assert_eq!(
+            view.to_string(),
+            "\x1b[1;31ma\x1b[0mb\nc\x1b[1;32md\x1b[0m\n"
+        );
+        assert_eq!(view.colour(false).to_string(), "ab\ncd\n");
+    }
+
+    #[test]
+    fn test_sparse_cells() {
+        let mut bits = BitGrid::new(3, 1);
+        bits.insert((1, 0));
+        let view =
+            Highlighted::with_cells(&bits, |v| if v.is_some() { "#" } else { "." }.to_string())
+                .colour(false);
+        assert_eq!(view.to_string(), ".#.\n");
+    }
+}
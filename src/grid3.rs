@@ -0,0 +1,336 @@
+//! A dense 3D grid, the voxel counterpart of [`Grid`](crate::grid::Grid).
+//!
+//! `x` grows east, `y` grows south as in 2D, and `z` grows up.
+
+use std::{
+    collections::VecDeque,
+    ops::{Index, IndexMut, Not},
+};
+
+use itertools::Itertools;
+
+/// A position in a [`Grid3`].
+pub type Pos3 = (usize, usize, usize);
+
+/// One of the 26 unit steps between neighbouring cells of a [`Grid3`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Direction3 {
+    dx: i8,
+    dy: i8,
+    dz: i8,
+}
+
+impl Direction3 {
+    pub const EAST: Self = Self::new(1, 0, 0);
+    pub const WEST: Self = Self::new(-1, 0, 0);
+    pub const SOUTH: Self = Self::new(0, 1, 0);
+    pub const NORTH: Self = Self::new(0, -1, 0);
+    pub const UP: Self = Self::new(0, 0, 1);
+    pub const DOWN: Self = Self::new(0, 0, -1);
+
+    const fn new(dx: i8, dy: i8, dz: i8) -> Self {
+        Self { dx, dy, dz }
+    }
+
+    /// The six directions that share a face, analogous to [`Direction::cardinal`].
+    ///
+    /// [`Direction::cardinal`]: crate::grid::Direction::cardinal
+    pub fn faces() -> [Self; 6] {
+        [
+            Self::EAST,
+            Self::WEST,
+            Self::SOUTH,
+            Self::NORTH,
+            Self::UP,
+            Self::DOWN,
+        ]
+    }
+
+    /// All 26 directions that share a face, an edge or a corner, analogous to
+    /// [`Direction::all`].
+    ///
+    /// [`Direction::all`]: crate::grid::Direction::all
+    pub fn all() -> [Self; 26] {
+        let mut all = [Self::EAST; 26];
+        (-1..=1)
+            .cartesian_product(-1..=1)
+            .cartesian_product(-1..=1)
+            .filter(|&((dx, dy), dz)| (dx, dy, dz) != (0, 0, 0))
+            .zip(all.iter_mut())
+            .for_each(|(((dx, dy), dz), slot)| *slot = Self::new(dx, dy, dz));
+        all
+    }
+
+    pub fn delta(self) -> (i64, i64, i64) {
+        (self.dx.into(), self.dy.into(), self.dz.into())
+    }
+
+    /// Move one cell, `None` when leaving a grid of the given `(width, height, depth)`.
+    pub fn step(self, pos: Pos3, bounds: Pos3) -> Option<Pos3> {
+        let axis = |value: usize, delta: i8, bound: usize| {
+            value
+                .checked_add_signed(delta.into())
+                .filter(|&v| v < bound)
+        };
+        Some((
+            axis(pos.0, self.dx, bounds.0)?,
+            axis(pos.1, self.dy, bounds.1)?,
+            axis(pos.2, self.dz, bounds.2)?,
+        ))
+    }
+}
+
+impl Not for Direction3 {
+    type Output = Self;
+
+    fn not(self) -> Self::Output {
+        Self::new(-self.dx, -self.dy, -self.dz)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid3<T> {
+    width: usize,
+    height: usize,
+    depth: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid3<T> {
+    /// Create a grid from `cells` ordered by `z`, then `y`, then `x`, returns `None` if
+    /// the number of cells does not match `width * height * depth`.
+    pub fn from_vec(width: usize, height: usize, depth: usize, cells: Vec<T>) -> Option<Self> {
+        (cells.len() == width * height * depth).then_some(Self {
+            width,
+            height,
+            depth,
+            cells,
+        })
+    }
+
+    pub fn filled(width: usize, height: usize, depth: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            width,
+            height,
+            depth,
+            cells: vec![value; width * height * depth],
+        }
+    }
+
+    pub fn new(width: usize, height: usize, depth: usize) -> Self
+    where
+        T: Default + Clone,
+    {
+        Self::filled(width, height, depth, T::default())
+    }
+
+    /// `(width, height, depth)`
+    pub fn dimensions(&self) -> Pos3 {
+        (self.width, self.height, self.depth)
+    }
+
+    fn offset(&self, (x, y, z): Pos3) -> Option<usize> {
+        (x < self.width && y < self.height && z < self.depth)
+            .then(|| (z * self.height + y) * self.width + x)
+    }
+
+    fn position(&self, offset: usize) -> Pos3 {
+        (
+            offset % self.width,
+            offset / self.width % self.height,
+            offset / (self.width * self.height),
+        )
+    }
+
+    pub fn get(&self, pos: Pos3) -> Option<&T> {
+        self.cells.get(self.offset(pos)?)
+    }
+
+    pub fn get_mut(&mut self, pos: Pos3) -> Option<&mut T> {
+        let offset = self.offset(pos)?;
+        self.cells.get_mut(offset)
+    }
+
+    /// Every cell with its position, ordered by `z`, then `y`, then `x`.
+    pub fn iter(&self) -> impl Iterator<Item = (Pos3, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .map(|(offset, cell)| (self.position(offset), cell))
+    }
+
+    pub fn step(&self, pos: Pos3, direction: Direction3) -> Option<Pos3> {
+        direction.step(pos, self.dimensions())
+    }
+
+    /// Neighbouring positions and their values, usually with [`Direction3::faces`] or
+    /// [`Direction3::all`].
+    pub fn neighbours<'a>(
+        &'a self,
+        pos: Pos3,
+        directions: impl IntoIterator<Item = Direction3> + 'a,
+    ) -> impl Iterator<Item = (Pos3, &'a T)> + 'a {
+        directions.into_iter().filter_map(move |dir| {
+            let next = self.step(pos, dir)?;
+            Some((next, self.get(next)?))
+        })
+    }
+
+    /// Cells reachable from any of `starts` through face neighbours where `passable`
+    /// holds, impassable starts are not included.
+    pub fn flood_fill(
+        &self,
+        starts: impl IntoIterator<Item = Pos3>,
+        passable: impl Fn(&T) -> bool,
+    ) -> Grid3<bool> {
+        let mut reached = Grid3::filled(self.width, self.height, self.depth, false);
+        let mut queue = VecDeque::new();
+        for start in starts {
+            if self.get(start).is_some_and(&passable) && !reached[start] {
+                reached[start] = true;
+                queue.push_back(start);
+            }
+        }
+        while let Some(pos) = queue.pop_front() {
+            for (next, cell) in self.neighbours(pos, Direction3::faces()) {
+                if passable(cell) && !reached[next] {
+                    reached[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        reached
+    }
+
+    /// Number of faces of `solid` cells that touch a non-solid cell or the edge of the
+    /// grid, enclosed air pockets included.
+    pub fn surface_area(&self, solid: impl Fn(&T) -> bool) -> usize {
+        self.exposed_faces(&solid, |next| next.is_none_or(|pos| !solid(&self[pos])))
+    }
+
+    /// Like [`Grid3::surface_area`], but only counting faces reachable from outside the
+    /// grid, so the insides of enclosed air pockets are ignored.
+    pub fn exterior_surface_area(&self, solid: impl Fn(&T) -> bool) -> usize {
+        let (width, height, depth) = self.dimensions();
+        let boundary = self.iter().map(|(pos, _)| pos).filter(|&(x, y, z)| {
+            x == 0 || y == 0 || z == 0 || x + 1 == width || y + 1 == height || z + 1 == depth
+        });
+        let outside = self.flood_fill(boundary, |cell| !solid(cell));
+        self.exposed_faces(&solid, |next| next.is_none_or(|pos| outside[pos]))
+    }
+
+    fn exposed_faces(
+        &self,
+        solid: &impl Fn(&T) -> bool,
+        exposed: impl Fn(Option<Pos3>) -> bool,
+    ) -> usize {
+        self.iter()
+            .filter(|(_, cell)| solid(cell))
+            .map(|(pos, _)| {
+                Direction3::faces()
+                    .into_iter()
+                    .filter(|&dir| exposed(self.step(pos, dir)))
+                    .count()
+            })
+            .sum()
+    }
+}
+
+impl Grid3<bool> {
+    /// A grid just large enough to hold every position in `points`, with those cells set.
+    pub fn from_points(points: impl IntoIterator<Item = Pos3>) -> Self {
+        let points = points.into_iter().collect_vec();
+        let bound = |axis: fn(&Pos3) -> usize| points.iter().map(axis).max().map_or(0, |m| m + 1);
+        let mut grid = Self::new(bound(|p| p.0), bound(|p| p.1), bound(|p| p.2));
+        points.into_iter().for_each(|pos| grid[pos] = true);
+        grid
+    }
+}
+
+impl<T> Index<Pos3> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, index: Pos3) -> &Self::Output {
+        match self.offset(index) {
+            Some(offset) => &self.cells[offset],
+            None => panic!(
+                "position {:?} out of bounds for grid of size {:?}",
+                index,
+                self.dimensions()
+            ),
+        }
+    }
+}
+
+impl<T> IndexMut<Pos3> for Grid3<T> {
+    fn index_mut(&mut self, index: Pos3) -> &mut Self::Output {
+        match self.offset(index) {
+            Some(offset) => &mut self.cells[offset],
+            None => panic!(
+                "position {:?} out of bounds for grid of size {:?}",
+                index,
+                self.dimensions()
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indexing_and_neighbours() {
+        let grid = Grid3::from_vec(2, 3, 4, (0..24).collect()).unwrap();
+        assert_eq!(grid[(1, 2, 3)], 23);
+        assert_eq!(grid[(1, 0, 1)], 7);
+        assert_eq!(grid.get((2, 0, 0)), None);
+        assert!(grid.iter().all(|(pos, &v)| grid[pos] == v));
+        assert_eq!(grid.neighbours((0, 0, 0), Direction3::faces()).count(), 3);
+        assert_eq!(grid.neighbours((0, 1, 1), Direction3::all()).count(), 17);
+        assert_eq!(Direction3::all().into_iter().unique().count(), 26);
+        assert_eq!(!Direction3::UP, Direction3::DOWN);
+        assert!(Grid3::<u8>::from_vec(2, 2, 2, vec![0; 7]).is_none());
+    }
+
+    #[test]
+    fn test_surface_area() {
+        // The example droplet from 2022 day 18, shifted so every coordinate is positive.
+        let cubes = [
+            (2, 2, 2),
+            (1, 2, 2),
+            (3, 2, 2),
+            (2, 1, 2),
+            (2, 3, 2),
+            (2, 2, 1),
+            (2, 2, 3),
+            (2, 2, 4),
+            (2, 2, 6),
+            (1, 2, 5),
+            (3, 2, 5),
+            (2, 1, 5),
+            (2, 3, 5),
+        ];
+        let droplet = Grid3::from_points(cubes);
+        assert_eq!(droplet.dimensions(), (4, 4, 7));
+        assert_eq!(droplet.surface_area(|&c| c), 64);
+        assert_eq!(droplet.exterior_surface_area(|&c| c), 58);
+        assert_eq!(
+            Grid3::from_points([(1, 1, 1), (2, 1, 1)]).surface_area(|&c| c),
+            10
+        );
+    }
+
+    #[test]
+    fn test_flood_fill() {
+        let mut shell = Grid3::filled(3, 3, 3, true);
+        shell[(1, 1, 1)] = false;
+        let outside = shell.flood_fill([(0, 0, 0)], |&solid| !solid);
+        assert!(outside.iter().all(|(_, &reached)| !reached));
+        let inside = shell.flood_fill([(1, 1, 1)], |&solid| !solid);
+        assert_eq!(inside.iter().filter(|(_, &reached)| reached).count(), 1);
+    }
+}
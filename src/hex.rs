@@ -0,0 +1,329 @@
+//! Hexagonal tiles in axial coordinates, alongside the square [`Grid`](crate::grid::Grid).
+//!
+//! Tiles are pointy-topped, so every tile has neighbours to the east and west and four
+//! diagonal ones. `q` grows east and `r` grows south-east, the third cube coordinate
+//! `s` is implied by `q + r + s == 0`.
+
+use std::{
+    collections::HashMap,
+    ops::{Add, AddAssign, Mul, Neg, Not, Sub},
+    str::FromStr,
+};
+
+use nom::{branch::alt, bytes::complete::tag, combinator::value, multi::many1, IResult};
+
+/// A hexagonal tile, or an offset between two tiles.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hex {
+    pub q: i64,
+    pub r: i64,
+}
+
+impl Hex {
+    pub const ORIGIN: Hex = Hex::new(0, 0);
+
+    pub const fn new(q: i64, r: i64) -> Self {
+        Self { q, r }
+    }
+
+    /// `None` unless the three cube coordinates sum to zero.
+    pub fn from_cube(q: i64, r: i64, s: i64) -> Option<Self> {
+        (q + r + s == 0).then_some(Self { q, r })
+    }
+
+    pub fn s(&self) -> i64 {
+        -self.q - self.r
+    }
+
+    /// `(q, r, s)`
+    pub fn cube(&self) -> (i64, i64, i64) {
+        (self.q, self.r, self.s())
+    }
+
+    pub fn step(self, direction: HexDirection) -> Self {
+        self + direction.offset()
+    }
+
+    /// The tile reached by taking every step of `path` in turn.
+    pub fn walk(self, path: impl IntoIterator<Item = HexDirection>) -> Self {
+        path.into_iter().fold(self, Self::step)
+    }
+
+    /// The six adjacent tiles, in the order of [`HexDirection::all`].
+    pub fn neighbours(self) -> impl Iterator<Item = Hex> {
+        HexDirection::all().into_iter().map(move |d| self.step(d))
+    }
+
+    /// Number of steps between two tiles.
+    pub fn distance(&self, other: &Self) -> i64 {
+        let (q, r, s) = (*self - *other).cube();
+        q.abs().max(r.abs()).max(s.abs())
+    }
+
+    /// Every tile exactly `radius` steps away, going anticlockwise from the east-most
+    /// of them. The ring of radius 0 is the tile itself.
+    pub fn ring(self, radius: u32) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![self];
+        }
+        let mut tile = self + HexDirection::East.offset() * radius as i64;
+        let mut ring = Vec::with_capacity(6 * radius as usize);
+        // Starting east, walking north-west takes the first side of the ring.
+        for direction in HexDirection::all().map(|d| d.turn(2)) {
+            for _ in 0..radius {
+                ring.push(tile);
+                tile = tile.step(direction);
+            }
+        }
+        ring
+    }
+
+    /// Every tile at most `radius` steps away, ring by ring from the centre.
+    pub fn spiral(self, radius: u32) -> impl Iterator<Item = Hex> {
+        (0..=radius).flat_map(move |r| self.ring(r))
+    }
+}
+
+impl From<(i64, i64)> for Hex {
+    fn from((q, r): (i64, i64)) -> Self {
+        Self { q, r }
+    }
+}
+
+impl Add for Hex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub for Hex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl Mul<i64> for Hex {
+    type Output = Self;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Self::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.q, -self.r)
+    }
+}
+
+use HexDirection::*;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    East,
+    NorthEast,
+    NorthWest,
+    West,
+    SouthWest,
+    SouthEast,
+}
+
+impl HexDirection {
+    /// All six directions, anticlockwise from east.
+    pub fn all() -> [HexDirection; 6] {
+        [East, NorthEast, NorthWest, West, SouthWest, SouthEast]
+    }
+
+    /// The offset of a single step in this direction.
+    pub fn offset(&self) -> Hex {
+        match self {
+            East => Hex::new(1, 0),
+            NorthEast => Hex::new(1, -1),
+            NorthWest => Hex::new(0, -1),
+            West => Hex::new(-1, 0),
+            SouthWest => Hex::new(-1, 1),
+            SouthEast => Hex::new(0, 1),
+        }
+    }
+
+    /// Turn anticlockwise by `sixths` multiples of 60°, negative values turn clockwise.
+    pub fn turn(&self, sixths: i32) -> HexDirection {
+        Self::all()[(*self as i32 + sixths).rem_euclid(6) as usize]
+    }
+
+    /// Parse one of `e`, `ne`, `nw`, `w`, `sw` and `se`.
+    pub fn parse(input: &str) -> IResult<&str, HexDirection> {
+        alt((
+            value(NorthEast, tag("ne")),
+            value(NorthWest, tag("nw")),
+            value(SouthEast, tag("se")),
+            value(SouthWest, tag("sw")),
+            value(East, tag("e")),
+            value(West, tag("w")),
+        ))(input)
+    }
+
+    /// Parse directions written without separators, such as `esenee`.
+    pub fn parse_path(input: &str) -> IResult<&str, Vec<HexDirection>> {
+        many1(Self::parse)(input)
+    }
+}
+
+impl FromStr for HexDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match Self::parse(s) {
+            Ok(("", direction)) => Ok(direction),
+            _ => Err(format!("invalid hex direction {:?}", s)),
+        }
+    }
+}
+
+impl Not for HexDirection {
+    type Output = HexDirection;
+
+    fn not(self) -> Self::Output {
+        self.turn(3)
+    }
+}
+
+/// Values stored by hexagonal tile, with the same `get` and `iter` shape as
+/// [`Grid`](crate::grid::Grid) but unbounded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HexGrid<T> {
+    cells: HashMap<Hex, T>,
+}
+
+impl<T> Default for HexGrid<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> HexGrid<T> {
+    pub fn new() -> Self {
+        Self {
+            cells: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, pos: impl Into<Hex>) -> Option<&T> {
+        self.cells.get(&pos.into())
+    }
+
+    pub fn get_mut(&mut self, pos: impl Into<Hex>) -> Option<&mut T> {
+        self.cells.get_mut(&pos.into())
+    }
+
+    /// Store `value` at `pos`, returning the value it replaced.
+    pub fn insert(&mut self, pos: impl Into<Hex>, value: T) -> Option<T> {
+        self.cells.insert(pos.into(), value)
+    }
+
+    pub fn remove(&mut self, pos: impl Into<Hex>) -> Option<T> {
+        self.cells.remove(&pos.into())
+    }
+
+    /// Every stored tile with its value, in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (Hex, &T)> {
+        self.cells.iter().map(|(&pos, value)| (pos, value))
+    }
+
+    /// Stored neighbours of `pos` and their values.
+    pub fn neighbours(&self, pos: impl Into<Hex>) -> impl Iterator<Item = (Hex, &T)> {
+        pos.into()
+            .neighbours()
+            .filter_map(|next| Some((next, self.get(next)?)))
+    }
+
+    /// Number of stored tiles.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+}
+
+impl<T> FromIterator<(Hex, T)> for HexGrid<T> {
+    fn from_iter<I: IntoIterator<Item = (Hex, T)>>(iter: I) -> Self {
+        Self {
+            cells: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_parse_and_walk() {
+        let (rest, path) = HexDirection::parse_path("esenee\n").unwrap();
+        assert_eq!(rest, "\n");
+        assert_eq!(path, [East, SouthEast, NorthEast, East]);
+        assert_eq!(Hex::ORIGIN.walk(path), Hex::new(3, 0));
+
+        let (_, back) = HexDirection::parse_path("nwwswee").unwrap();
+        assert_eq!(Hex::ORIGIN.walk(back), Hex::ORIGIN);
+        assert_eq!("sw".parse(), Ok(SouthWest));
+        assert!("n".parse::<HexDirection>().is_err());
+        assert!(HexDirection::parse_path("x").is_err());
+    }
+
+    #[test]
+    fn test_turn() {
+        assert_eq!(East.turn(1), NorthEast);
+        assert_eq!(East.turn(-1), SouthEast);
+        assert_eq!(SouthWest.turn(9), NorthEast);
+        assert!(HexDirection::all()
+            .iter()
+            .all(|d| d.turn(3).turn(3) == *d && d.turn(6) == *d));
+    }
+
+    #[test]
+    fn test_distance_and_rings() {
+        let a = Hex::new(1, -3);
+        assert_eq!(a.cube(), (1, -3, 2));
+        assert_eq!(Hex::from_cube(1, -3, 2), Some(a));
+        assert_eq!(Hex::from_cube(1, 1, 1), None);
+        assert_eq!(a.distance(&Hex::ORIGIN), 3);
+        assert_eq!(Hex::ORIGIN.distance(&Hex::new(-2, -1)), 3);
+
+        let ring = a.ring(2);
+        assert_eq!(ring.len(), 12);
+        assert_eq!(ring.iter().unique().count(), 12);
+        assert!(ring.iter().all(|h| h.distance(&a) == 2));
+        assert_eq!(a.spiral(2).count(), 19);
+        assert!(HexDirection::all()
+            .iter()
+            .all(|&d| Hex::ORIGIN.step(d).step(!d) == Hex::ORIGIN));
+    }
+
+    #[test]
+    fn test_hex_grid() {
+        let mut grid: HexGrid<bool> = Hex::ORIGIN.ring(1).into_iter().map(|h| (h, true)).collect();
+        assert_eq!(grid.len(), 6);
+        assert_eq!(grid.neighbours(Hex::ORIGIN).count(), 6);
+        assert_eq!(grid.neighbours(Hex::new(1, 0)).count(), 2);
+        assert_eq!(grid.insert((0, 0), false), None);
+        assert_eq!(grid.get((0, 0)), Some(&false));
+        assert_eq!(grid.remove((1, 0)), Some(true));
+        assert_eq!(grid.iter().filter(|(_, &v)| v).count(), 5);
+    }
+}
@@ -0,0 +1,222 @@
+//! Run and time the solutions without `cargo aoc`.
+//!
+//! ```text
+//! aoc2024 run --day 7 --part 2 --input input/2024/day7.txt
+//! aoc2024 run --day 7 --input - < day7.txt
+//! aoc2024 run --all
+//! ```
+
+use std::{
+    io,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use aoc2024::runner::{self, Solution};
+
+const USAGE: &str = "\
+usage: aoc2024 run --day <day> [--part <part>] [--input <path>]
+       aoc2024 run --all
+
+Inputs are read from input/2024/day<day>.txt unless --input is given, use
+--input - to read from stdin.";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Input {
+    Default,
+    File(PathBuf),
+    Stdin,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Day {
+        day: u32,
+        part: Option<u32>,
+        input: Input,
+    },
+    All,
+    Help,
+}
+
+fn number(flag: &str, value: Option<String>) -> Result<u32, String> {
+    let value = value.ok_or_else(|| format!("{} needs a value", flag))?;
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, not {:?}", flag, value))
+}
+
+/// Set `option` to what `flag` is given, which may only happen once.
+fn once(flag: &str, option: &mut Option<u32>, value: Option<String>) -> Result<(), String> {
+    if option.is_some() {
+        return Err(format!("{} is given more than once", flag));
+    }
+    *option = Some(number(flag, value)?);
+    Ok(())
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> Result<Command, String> {
+    let mut args = args.into_iter();
+    match args.next().as_deref() {
+        Some("run") => {}
+        Some("help" | "--help" | "-h") | None => return Ok(Command::Help),
+        Some(command) => return Err(format!("unknown command {:?}", command)),
+    }
+    let (mut all, mut day, mut part, mut input) = (false, None, None, Input::Default);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--all" => all = true,
+            "--day" => once("--day", &mut day, args.next())?,
+            "--part" => once("--part", &mut part, args.next())?,
+            "--input" => {
+                input = match args.next().as_deref() {
+                    Some("-") => Input::Stdin,
+                    Some(path) => Input::File(path.into()),
+                    None => return Err("--input needs a path".to_string()),
+                }
+            }
+            "--help" | "-h" => return Ok(Command::Help),
+            arg => return Err(format!("unknown argument {:?}", arg)),
+        }
+    }
+    match (all, day) {
+        (true, None) if part.is_none() && input == Input::Default => Ok(Command::All),
+        (true, _) => Err("--all runs every day and part from the default inputs".to_string()),
+        (false, Some(day)) => Ok(Command::Day { day, part, input }),
+        (false, None) => Err("either --day or --all is needed".to_string()),
+    }
+}
+
+fn default_input(day: u32) -> PathBuf {
+    Path::new("input/2024").join(format!("day{}.txt", day))
+}
+
+fn read_input(day: u32, input: &Input) -> Result<String, String> {
+    let path = match input {
+        Input::Default => default_input(day),
+        Input::File(path) => path.clone(),
+        Input::Stdin => {
+            return io::read_to_string(io::stdin())
+                .map_err(|e| format!("could not read stdin: {}", e))
+        }
+    };
+    std::fs::read_to_string(&path).map_err(|e| format!("could not read {}: {}", path.display(), e))
+}
+
+/// Print the answer and timings of every solution, false if any of them failed.
+fn report(solutions: &[Solution], input: &str) -> bool {
+    let mut ok = true;
+    for solution in solutions {
+        let outcome = solution.run(input);
+        let label = format!("day {} part {}", solution.day, solution.part);
+        let timing = format!("parse {:?}, solve {:?}", outcome.parse, outcome.solve);
+        match outcome.answer {
+            Ok(answer) => println!("{}: {} ({})", label, answer, timing),
+            Err(error) => {
+                println!("{}: failed ({})\n{}", label, timing, error);
+                ok = false;
+            }
+        }
+    }
+    ok
+}
+
+fn run(command: Command) -> Result<bool, String> {
+    match command {
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(true)
+        }
+        Command::Day { day, part, input } => {
+            let solutions = runner::find(day, part);
+            if solutions.is_empty() {
+                return Err(match part {
+                    Some(part) => format!("there is no solution for day {} part {}", day, part),
+                    None => format!("there is no solution for day {}", day),
+                });
+            }
+            Ok(report(&solutions, &read_input(day, &input)?))
+        }
+        Command::All => {
+            let solutions = runner::solutions();
+            let mut ok = true;
+            for day in solutions.chunk_by(|a, b| a.day == b.day) {
+                match read_input(day[0].day, &Input::Default) {
+                    Ok(input) => ok &= report(day, &input),
+                    Err(error) => eprintln!("skipping day {}, {}", day[0].day, error),
+                }
+            }
+            Ok(ok)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let command = match parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    match run(command) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(error) => {
+            eprintln!("{}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Command, String> {
+        parse_args(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse_args() {
+        assert_eq!(
+            parse("run --day 7 --part 2 --input day7.txt"),
+            Ok(Command::Day {
+                day: 7,
+                part: Some(2),
+                input: Input::File("day7.txt".into()),
+            })
+        );
+        assert_eq!(
+            parse("run --input - --day 7"),
+            Ok(Command::Day {
+                day: 7,
+                part: None,
+                input: Input::Stdin,
+            })
+        );
+        assert_eq!(parse("run --all"), Ok(Command::All));
+        assert_eq!(parse(""), Ok(Command::Help));
+        assert_eq!(parse("run --day 7 --help"), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_parse_args_errors() {
+        assert!(parse("walk --day 7").is_err());
+        assert!(parse("run").is_err());
+        assert!(parse("run --day").is_err());
+        assert!(parse("run --day seven").is_err());
+        assert!(parse("run --all --day 7").is_err());
+        assert!(parse("run --all --input -").is_err());
+        assert!(parse("run --day 7 --verbose").is_err());
+        assert_eq!(
+            parse("run --day 7 --day 8"),
+            Err("--day is given more than once".to_string())
+        );
+        assert_eq!(
+            parse("run --day 7 --part 1 --part 2"),
+            Err("--part is given more than once".to_string())
+        );
+        assert_eq!(default_input(9), Path::new("input/2024/day9.txt"));
+    }
+}
@@ -0,0 +1,391 @@
+//! nom combinators for the shapes puzzle inputs keep coming in, all working on [`Span`]
+//! so that [`error::parse`](crate::error::parse) can point at whatever went wrong.
+
+use std::fmt::Display;
+
+use nom::{
+    bytes::complete::tag,
+    character::complete::{char, digit1, line_ending, one_of},
+    combinator::{cut, map, map_res, opt, recognize},
+    error::{ErrorKind, ParseError},
+    sequence::{pair, preceded, separated_pair},
+    InputTake,
+};
+
+use crate::{
+    error::{grid_error, AocError, ParseResult, Span, SpanError},
+    grid::Grid,
+    point::Vector,
+};
+
+/// An unsigned number, an error rather than a wrapped value when it is too large.
+pub fn unsigned(input: Span) -> ParseResult<u64> {
+    map_res(digit1, |digits: Span| {
+        digits.fragment().parse().map_err(|_| "number too large")
+    })(input)
+}
+
+/// A number that may be negative, an error rather than a wrapped value when it is too
+/// large.
+pub fn signed(input: Span) -> ParseResult<i64> {
+    map_res(recognize(pair(opt(char('-')), digit1)), |digits: Span| {
+        digits.fragment().parse().map_err(|_| "number too large")
+    })(input)
+}
+
+/// One or more numbers split by whatever `separator` parses, which has to be followed by
+/// another number.
+fn number_list<'a, S, O>(
+    mut separator: impl FnMut(Span<'a>) -> ParseResult<'a, S>,
+    mut number: impl FnMut(Span<'a>) -> ParseResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> ParseResult<'a, Vec<O>> {
+    move |input| {
+        let (mut input, first) = number(input)?;
+        let mut numbers = vec![first];
+        while let Ok((next, _)) = separator(input) {
+            let (next, value) = cut(&mut number)(next)?;
+            numbers.push(value);
+            input = next;
+        }
+        Ok((input, numbers))
+    }
+}
+
+/// Unsigned numbers such as `7,3,12`, split by whatever `separator` parses.
+pub fn unsigned_list<'a, S>(
+    separator: impl FnMut(Span<'a>) -> ParseResult<'a, S>,
+) -> impl FnMut(Span<'a>) -> ParseResult<'a, Vec<u64>> {
+    number_list(separator, unsigned)
+}
+
+/// Numbers that may be negative, such as `7 -3 12`, split by whatever `separator` parses.
+pub fn signed_list<'a, S>(
+    separator: impl FnMut(Span<'a>) -> ParseResult<'a, S>,
+) -> impl FnMut(Span<'a>) -> ParseResult<'a, Vec<i64>> {
+    number_list(separator, signed)
+}
+
+/// Whether the line `input` starts has nothing but whitespace on it.
+fn is_blank(input: Span) -> bool {
+    input
+        .fragment()
+        .split('\n')
+        .next()
+        .is_none_or(|line| line.trim().is_empty())
+}
+
+/// One or more lines, each parsed by `parser`, up to a blank line or the end of the input.
+/// Every line before that has to parse as a whole, so that errors point into the line
+/// that is wrong rather than at where the lines stop.
+pub fn lines<'a, O>(
+    mut parser: impl FnMut(Span<'a>) -> ParseResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> ParseResult<'a, Vec<O>> {
+    move |input| {
+        let (mut input, first) = parser(input)?;
+        let mut lines = vec![first];
+        loop {
+            let next = match line_ending::<_, SpanError>(input) {
+                Ok((next, _)) => next,
+                Err(_) if input.is_empty() => break,
+                Err(_) => {
+                    let error = SpanError::from_error_kind(input, ErrorKind::CrLf);
+                    return Err(nom::Err::Failure(error));
+                }
+            };
+            if is_blank(next) {
+                break;
+            }
+            let (next, line) = cut(&mut parser)(next)?;
+            lines.push(line);
+            input = next;
+        }
+        Ok((input, lines))
+    }
+}
+
+/// The end of a line followed by an empty line, which separates blocks of input.
+pub fn blank_line(input: Span) -> ParseResult<()> {
+    map(pair(line_ending, line_ending), |_| ())(input)
+}
+
+/// One or more blocks separated by blank lines, each parsed by `parser`. Like [`lines`],
+/// a block that starts after a blank line has to parse.
+pub fn blocks<'a, O>(
+    mut parser: impl FnMut(Span<'a>) -> ParseResult<'a, O>,
+) -> impl FnMut(Span<'a>) -> ParseResult<'a, Vec<O>> {
+    move |input| {
+        let (mut input, first) = parser(input)?;
+        let mut blocks = vec![first];
+        while let Ok((next, _)) = blank_line(input) {
+            if next.fragment().trim().is_empty() {
+                break;
+            }
+            let (next, block) = cut(&mut parser)(next)?;
+            blocks.push(block);
+            input = next;
+        }
+        Ok((input, blocks))
+    }
+}
+
+/// A coordinate named as in `X=3` or `X+3`.
+fn axis<'a>(name: char) -> impl FnMut(Span<'a>) -> ParseResult<'a, i64> {
+    preceded(pair(char(name), one_of("=+")), signed)
+}
+
+/// A pair of coordinates named by their axis, such as `X+94, Y=-34`.
+pub fn axis_coordinates(input: Span) -> ParseResult<Vector> {
+    map(
+        separated_pair(axis('X'), tag(", "), axis('Y')),
+        Vector::from,
+    )(input)
+}
+
+/// A bare pair of coordinates such as `3,-4`.
+pub fn coordinates(input: Span) -> ParseResult<Vector> {
+    map(separated_pair(signed, char(','), signed), Vector::from)(input)
+}
+
+/// Bare coordinates labelled by `key`, such as `p=3,-4`.
+pub fn labelled_coordinates<'a>(key: &'a str) -> impl FnMut(Span<'a>) -> ParseResult<'a, Vector> {
+    preceded(pair(tag(key), char('=')), coordinates)
+}
+
+/// Where `line` and `column`, counted from 1, are in `input`.
+fn locate(input: Span, line: usize, column: usize) -> Span {
+    let text = input.fragment();
+    let line_start: usize = text
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum();
+    let offset: usize = text[line_start..]
+        .chars()
+        .take(column - 1)
+        .map(char::len_utf8)
+        .sum();
+    input.take_split(line_start + offset).0
+}
+
+/// A rectangular grid, one row per line up to a blank line or the end of the input, read
+/// by [`Grid::parse`] with `cell` turning characters into cells. Its errors are reported
+/// at the line and column they are found, like [`AocError::from_grid`] does.
+pub fn grid<'a, T, E>(
+    mut cell: impl FnMut(char) -> Result<T, E>,
+) -> impl FnMut(Span<'a>) -> ParseResult<'a, Grid<T>>
+where
+    E: Display,
+{
+    move |input: Span<'a>| {
+        let length: usize = input
+            .fragment()
+            .split_inclusive('\n')
+            .take_while(|line| !line.trim().is_empty())
+            .map(str::len)
+            .sum();
+        let rows = input.fragment()[..length].trim_end_matches(['\r', '\n']);
+        if rows.is_empty() {
+            return Err(nom::Err::Error(SpanError::new(input, "expected a grid")));
+        }
+        let grid = Grid::parse(rows, &mut cell).map_err(|error| {
+            let (line, column, message) = grid_error(error);
+            nom::Err::Failure(SpanError::new(locate(input, line, column), message))
+        })?;
+        Ok((input.take_split(rows.len()).0, grid))
+    }
+}
+
+/// Every integer in `input`, in order, ignoring whatever is around them. A `-` makes the
+/// number negative unless it follows a letter or digit, so `5-3` is `5` and `3`, and a
+/// number too large for an `i64` is an error rather than being left out.
+pub fn integers(input: &str) -> Result<Vec<i64>, AocError> {
+    let mut numbers = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let mut rest = line;
+        while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+            let end = rest[start..]
+                .find(|c: char| !c.is_ascii_digit())
+                .map_or(rest.len(), |length| start + length);
+            let before = &line[..line.len() - rest.len() + start];
+            let start = match before.strip_suffix('-') {
+                Some(before) if !before.ends_with(|c: char| c.is_alphanumeric()) => start - 1,
+                _ => start,
+            };
+            let number = rest[start..end].parse().map_err(|_| {
+                let column = line[..line.len() - rest.len() + start].chars().count() + 1;
+                AocError::at(input, index + 1, column, "number too large")
+            })?;
+            numbers.push(number);
+            rest = &rest[end..];
+        }
+    }
+    Ok(numbers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::parse;
+    use indoc::indoc;
+
+    #[test]
+    fn test_number_lists() {
+        let input = "1 2 3\n4 5\n";
+        assert_eq!(
+            parse(input, "numbers", lines(unsigned_list(char(' ')))),
+            Ok(vec![vec![1, 2, 3], vec![4, 5]])
+        );
+        assert_eq!(
+            parse("7,-3,12", "numbers", signed_list(char(','))),
+            Ok(vec![7, -3, 12])
+        );
+        assert_eq!(
+            parse("7,-3", "numbers", unsigned_list(char(',')))
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 3: expected a number\n7,-3\n  ^"
+        );
+        assert_eq!(
+            parse(
+                "1 2\n3 99999999999999999999\n",
+                "numbers",
+                lines(unsigned_list(char(' ')))
+            )
+            .unwrap_err()
+            .to_string(),
+            "line 2, column 3: number too large\n3 99999999999999999999\n  ^"
+        );
+        assert_eq!(
+            parse("1 2\n3 4 x\n", "numbers", lines(signed_list(char(' '))))
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 5: expected a number\n3 4 x\n    ^"
+        );
+        assert_eq!(
+            parse("1\n2 3\n", "numbers", lines(unsigned))
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 2: expected a line ending\n2 3\n ^"
+        );
+        assert_eq!(
+            parse("1\n2\n\n  \n", "numbers", lines(unsigned)),
+            Ok(vec![1, 2])
+        );
+    }
+
+    #[test]
+    fn test_blocks() {
+        let input = indoc! {"
+            1|2
+            3|4
+
+            1,2
+            3
+        "};
+        let rules = lines(separated_pair(unsigned, tag("|"), unsigned));
+        let (rules, updates) = parse(
+            input,
+            "rules and updates",
+            separated_pair(rules, blank_line, lines(unsigned_list(char(',')))),
+        )
+        .unwrap();
+        assert_eq!(rules, [(1, 2), (3, 4)]);
+        assert_eq!(updates, [vec![1, 2], vec![3]]);
+
+        let input = "1\n2\n\n3\n\n4\n";
+        assert_eq!(
+            parse(input, "blocks", blocks(lines(unsigned))),
+            Ok(vec![vec![1, 2], vec![3], vec![4]])
+        );
+        assert_eq!(
+            parse("1\n2\n\n3\nx\n", "blocks", blocks(lines(unsigned)))
+                .unwrap_err()
+                .to_string(),
+            "line 5, column 1: expected a number\nx\n^"
+        );
+        assert_eq!(
+            parse("1\n2\n\nx\n", "blocks", blocks(lines(unsigned)))
+                .unwrap_err()
+                .to_string(),
+            "line 4, column 1: expected a number\nx\n^"
+        );
+    }
+
+    #[test]
+    fn test_coordinates() {
+        let parse_one = |input| parse(input, "coordinates", coordinates);
+        assert_eq!(parse_one("3,-4"), Ok(Vector::new(3, -4)));
+        assert!(parse_one("3, -4").is_err());
+        assert!(parse_one("X=3,Y=4").is_err());
+        assert!(parse_one("3;4").is_err());
+
+        let parse_axes = |input| parse(input, "coordinates", axis_coordinates);
+        assert_eq!(parse_axes("X+94, Y=-34"), Ok(Vector::new(94, -34)));
+        assert!(parse_axes("94, -34").is_err());
+        assert!(parse_axes("X=94, 34").is_err());
+        assert!(parse_axes("Y=94, X=34").is_err());
+
+        let robot = separated_pair(
+            labelled_coordinates("p"),
+            char(' '),
+            labelled_coordinates("v"),
+        );
+        assert_eq!(
+            parse("p=0,4 v=3,-3", "robot", robot),
+            Ok((Vector::new(0, 4), Vector::new(3, -3)))
+        );
+        assert!(parse("q=0,4", "robot", labelled_coordinates("p")).is_err());
+        assert!(parse("p=X=3,Y=4", "robot", labelled_coordinates("p")).is_err());
+    }
+
+    #[test]
+    fn test_grid() {
+        let digit = |c: char| c.to_digit(10).ok_or("expected a digit");
+        let input = "012\n345\n\nrest";
+        let (parsed, rest) = parse(
+            input,
+            "grid",
+            separated_pair(grid(digit), blank_line, tag("rest")),
+        )
+        .unwrap();
+        assert_eq!(parsed.dimensions(), (3, 2));
+        assert_eq!(parsed[(2, 1)], 5);
+        assert_eq!(*rest.fragment(), "rest");
+
+        assert_eq!(
+            parse("012\n34\n", "grid", grid(digit))
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 3: expected 3 characters, found 2\n34\n  ^"
+        );
+        assert_eq!(
+            parse("012\r\n0x2\r\n", "grid", grid(digit))
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 2: 'x': expected a digit\n0x2\n ^"
+        );
+        assert_eq!(
+            parse("\n\n012", "grid", grid(digit))
+                .unwrap_err()
+                .to_string(),
+            "line 1, column 1: expected a grid\n\n^"
+        );
+    }
+
+    #[test]
+    fn test_integers() {
+        assert_eq!(
+            integers("Sensor at x=2, y=-18: beacon at x=-2,y=15"),
+            Ok(vec![2, -18, -2, 15])
+        );
+        assert_eq!(integers("5-3, 4 - 1, -7"), Ok(vec![5, 3, 4, 1, -7]));
+        assert_eq!(integers("1\n-2 x\n"), Ok(vec![1, -2]));
+        assert_eq!(integers("no numbers - here"), Ok(vec![]));
+        assert_eq!(
+            integers("7\nx=-99999999999999999999")
+                .unwrap_err()
+                .to_string(),
+            "line 2, column 3: number too large\nx=-99999999999999999999\n  ^"
+        );
+    }
+}
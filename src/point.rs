@@ -0,0 +1,217 @@
+//! Points and offsets on a 2D lattice, convertible to and from `(x, y)` tuples.
+
+use std::{
+    fmt::Display,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+/// A position or offset on a 2D lattice, `x` grows east and `y` grows south.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// A signed offset between two points.
+pub type Vector = Point<i64>;
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+
+    /// Convert both coordinates, `None` if either does not fit in `U`.
+    pub fn try_cast<U>(self) -> Option<Point<U>>
+    where
+        U: TryFrom<T>,
+    {
+        Some(Point {
+            x: U::try_from(self.x).ok()?,
+            y: U::try_from(self.y).ok()?,
+        })
+    }
+}
+
+impl<T> Point<T>
+where
+    T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T>,
+{
+    fn abs_diff(a: T, b: T) -> T {
+        if a > b {
+            a - b
+        } else {
+            b - a
+        }
+    }
+
+    pub fn manhattan(&self, other: &Self) -> T {
+        Self::abs_diff(self.x, other.x) + Self::abs_diff(self.y, other.y)
+    }
+
+    pub fn chebyshev(&self, other: &Self) -> T {
+        let dx = Self::abs_diff(self.x, other.x);
+        let dy = Self::abs_diff(self.y, other.y);
+        if dx > dy {
+            dx
+        } else {
+            dy
+        }
+    }
+}
+
+impl Point<usize> {
+    pub fn to_signed(self) -> Option<Vector> {
+        self.try_cast()
+    }
+
+    /// Move by `offset`, `None` if either coordinate would become negative.
+    pub fn checked_add_signed(self, offset: Vector) -> Option<Self> {
+        (self.to_signed()? + offset).to_unsigned()
+    }
+}
+
+impl Vector {
+    /// `None` if either coordinate is negative.
+    pub fn to_unsigned(self) -> Option<Point<usize>> {
+        self.try_cast()
+    }
+}
+
+impl<T> From<(T, T)> for Point<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T> From<Point<T>> for (T, T) {
+    fn from(point: Point<T>) -> Self {
+        (point.x, point.y)
+    }
+}
+
+impl<T> Display for Point<T>
+where
+    T: Display,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+impl<T> Add for Point<T>
+where
+    T: Add<Output = T>,
+{
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+impl<T> AddAssign for Point<T>
+where
+    T: AddAssign,
+{
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T> Sub for Point<T>
+where
+    T: Sub<Output = T>,
+{
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl<T> SubAssign for Point<T>
+where
+    T: SubAssign,
+{
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T> Mul<T> for Point<T>
+where
+    T: Mul<Output = T> + Copy,
+{
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self {
+            x: self.x * rhs,
+            y: self.y * rhs,
+        }
+    }
+}
+
+impl<T> Neg for Point<T>
+where
+    T: Neg<Output = T>,
+{
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Vector::new(4, 3);
+        let b = Vector::new(5, 5);
+        assert_eq!(a + (b - a) * 2, Vector::new(6, 7));
+        assert_eq!(-a, Vector::new(-4, -3));
+        let mut c = a;
+        c += b;
+        c -= Vector::new(1, 1);
+        assert_eq!(c, Vector::new(8, 7));
+    }
+
+    #[test]
+    fn test_distances() {
+        let a = Point::new(1usize, 7);
+        let b = Point::new(4usize, 2);
+        assert_eq!(a.manhattan(&b), 8);
+        assert_eq!(a.chebyshev(&b), 5);
+        assert_eq!(Vector::new(-2, 3).manhattan(&Vector::new(2, -3)), 10);
+    }
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(Vector::new(2, 0).to_unsigned(), Some(Point::new(2, 0)));
+        assert_eq!(Vector::new(2, -1).to_unsigned(), None);
+        assert_eq!(Point::new(3usize, 1).to_signed(), Some(Vector::new(3, 1)));
+        assert_eq!(
+            Point::new(0usize, 1).checked_add_signed(Vector::new(-1, 0)),
+            None
+        );
+        assert_eq!(
+            Point::<u8>::from((1, 2)).try_cast::<i8>(),
+            Some(Point::new(1, 2))
+        );
+        assert_eq!(<(usize, usize)>::from(Point::new(1, 2)), (1, 2));
+    }
+}
@@ -0,0 +1,236 @@
+//! Connected-component labeling of a [`Grid`] and the geometry of each region.
+
+use crate::grid::{
+    Direction::{self, *},
+    Grid,
+};
+
+/// The two edges and the diagonal meeting in each corner of a cell.
+const CORNERS: [(Direction, Direction, Direction); 4] = [
+    (North, East, NorthEast),
+    (East, South, SouthEast),
+    (South, West, SouthWest),
+    (West, North, NorthWest),
+];
+
+/// A connected group of cells holding the same value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region<T> {
+    value: T,
+    cells: Vec<(usize, usize)>,
+    perimeter: usize,
+    sides: usize,
+    bounding_box: ((usize, usize), (usize, usize)),
+}
+
+impl<T> Region<T> {
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    pub fn cells(&self) -> &[(usize, usize)] {
+        &self.cells
+    }
+
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Number of cell edges that border another region or the edge of the grid.
+    pub fn perimeter(&self) -> usize {
+        self.perimeter
+    }
+
+    /// Number of straight fence segments around the region, holes included.
+    pub fn sides(&self) -> usize {
+        self.sides
+    }
+
+    /// Inclusive `(min, max)` corners of the smallest rectangle holding every cell.
+    pub fn bounding_box(&self) -> ((usize, usize), (usize, usize)) {
+        self.bounding_box
+    }
+}
+
+/// Every region of a grid, along with a grid of region indices.
+#[derive(Debug, Clone)]
+pub struct Regions<T> {
+    labels: Grid<usize>,
+    regions: Vec<Region<T>>,
+}
+
+impl<T> Regions<T> {
+    /// Index into [`Regions::regions`] for every position.
+    pub fn labels(&self) -> &Grid<usize> {
+        &self.labels
+    }
+
+    pub fn regions(&self) -> &[Region<T>] {
+        &self.regions
+    }
+
+    pub fn region_at(&self, pos: (usize, usize)) -> Option<&Region<T>> {
+        self.regions.get(*self.labels.get(pos)?)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Region<T>> {
+        self.regions.iter()
+    }
+}
+
+impl<T> Grid<T>
+where
+    T: PartialEq + Clone,
+{
+    /// Group equal neighbouring cells, `connectivity` is usually [`Direction::cardinal`]
+    /// or [`Direction::all`].
+    ///
+    /// Perimeter and sides are always measured along the four cardinal edges of each
+    /// cell, so with diagonal connectivity two cells touching at a corner count as a
+    /// corner of the region outline.
+    pub fn regions(&self, connectivity: &[Direction]) -> Regions<T> {
+        let mut labels =
+            Grid::filled(self.width(), self.height(), usize::MAX).with_topology(self.topology());
+        let mut members = Vec::new();
+        for (start, value) in self.iter() {
+            if labels[start] != usize::MAX {
+                continue;
+            }
+            let label = members.len();
+            let mut cells = Vec::new();
+            let mut stack = vec![start];
+            labels[start] = label;
+            while let Some(pos) = stack.pop() {
+                cells.push(pos);
+                for (next, next_value) in self.neighbours(pos, connectivity.iter().copied()) {
+                    if labels[next] == usize::MAX && next_value == value {
+                        labels[next] = label;
+                        stack.push(next);
+                    }
+                }
+            }
+            members.push((value.clone(), cells));
+        }
+
+        let regions = members
+            .into_iter()
+            .enumerate()
+            .map(|(label, (value, mut cells))| {
+                cells.sort_unstable_by_key(|&(x, y)| (y, x));
+                let inside = |pos: (usize, usize), dir: Direction| {
+                    labels
+                        .step(pos, dir)
+                        .is_some_and(|next| labels[next] == label)
+                };
+                let perimeter = cells
+                    .iter()
+                    .map(|&pos| {
+                        Direction::cardinal()
+                            .into_iter()
+                            .filter(|&dir| !inside(pos, dir))
+                            .count()
+                    })
+                    .sum();
+                // A polygon has as many sides as corners, so count the convex and concave
+                // corners of every cell instead of tracing the outline.
+                let sides = cells
+                    .iter()
+                    .map(|&pos| {
+                        CORNERS
+                            .into_iter()
+                            .filter(|&(a, b, diagonal)| match (inside(pos, a), inside(pos, b)) {
+                                (false, false) => true,
+                                (true, true) => !inside(pos, diagonal),
+                                _ => false,
+                            })
+                            .count()
+                    })
+                    .sum();
+                let bounding_box = cells.iter().fold(
+                    ((usize::MAX, usize::MAX), (0, 0)),
+                    |((min_x, min_y), (max_x, max_y)), &(x, y)| {
+                        ((min_x.min(x), min_y.min(y)), (max_x.max(x), max_y.max(y)))
+                    },
+                );
+                Region {
+                    value,
+                    cells,
+                    perimeter,
+                    sides,
+                    bounding_box,
+                }
+            })
+            .collect();
+
+        Regions { labels, regions }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    fn fence_prices(input: &str) -> (usize, usize) {
+        let grid = Grid::parse_chars(input).unwrap();
+        let regions = grid.regions(&Direction::cardinal());
+        (
+            regions.iter().map(|r| r.area() * r.perimeter()).sum(),
+            regions.iter().map(|r| r.area() * r.sides()).sum(),
+        )
+    }
+
+    #[test]
+    fn test_small_garden() {
+        let input = indoc! {"
+            AAAA
+            BBCD
+            BBCC
+            EEEC
+        "};
+        assert_eq!(fence_prices(input), (140, 80));
+
+        let grid = Grid::parse_chars(input).unwrap();
+        let regions = grid.regions(&Direction::cardinal());
+        assert_eq!(regions.regions().len(), 5);
+        let c = regions.region_at((2, 1)).unwrap();
+        assert_eq!(c.value(), &'C');
+        assert_eq!(c.area(), 4);
+        assert_eq!(c.perimeter(), 10);
+        assert_eq!(c.sides(), 8);
+        assert_eq!(c.bounding_box(), ((2, 1), (3, 3)));
+        assert_eq!(c.cells(), &[(2, 1), (2, 2), (3, 2), (3, 3)]);
+    }
+
+    #[test]
+    fn test_holes_and_diagonals() {
+        let e_shape = indoc! {"
+            EEEEE
+            EXXXX
+            EEEEE
+            EXXXX
+            EEEEE
+        "};
+        assert_eq!(fence_prices(e_shape).1, 236);
+
+        let touching = indoc! {"
+            AAAAAA
+            AAABBA
+            AAABBA
+            ABBAAA
+            ABBAAA
+            AAAAAA
+        "};
+        assert_eq!(fence_prices(touching).1, 368);
+    }
+
+    #[test]
+    fn test_diagonal_connectivity() {
+        let grid = Grid::parse_chars("X.\n.X\n").unwrap();
+        assert_eq!(grid.regions(&Direction::cardinal()).regions().len(), 4);
+        let regions = grid.regions(&Direction::all());
+        assert_eq!(regions.regions().len(), 2);
+        assert_eq!(regions.region_at((1, 1)).unwrap().area(), 2);
+        assert_eq!(regions.region_at((1, 1)).unwrap().perimeter(), 8);
+    }
+}
@@ -0,0 +1,141 @@
+//! Every solution by day and part, timed, so they can be run without `cargo aoc`.
+
+use std::{
+    borrow::Borrow,
+    fmt::Display,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    day01, day02, day03, day04, day05, day06, day07, day08, day09, day10, day11, day13, day14,
+    error::AocError,
+};
+
+/// One part of one day.
+#[derive(Debug, Clone, Copy)]
+pub struct Solution {
+    pub day: u32,
+    pub part: u32,
+    run: fn(&str) -> Outcome,
+}
+
+/// The answer of a [`Solution`], with the time spent parsing and solving.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub answer: Result<String, AocError>,
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+impl Solution {
+    pub(crate) const fn new(day: u32, part: u32, run: fn(&str) -> Outcome) -> Self {
+        Self { day, part, run }
+    }
+
+    pub fn run(&self, input: &str) -> Outcome {
+        (self.run)(input)
+    }
+}
+
+/// Time `generator` on `input` and `solver` on what it generated. Solving is skipped,
+/// and takes no time, when the input does not parse.
+pub(crate) fn timed<'a, I, B, O>(
+    input: &'a str,
+    generator: impl Fn(&'a str) -> Result<I, AocError>,
+    solver: impl Fn(&B) -> Result<O, AocError>,
+) -> Outcome
+where
+    I: Borrow<B>,
+    B: ?Sized,
+    O: Display,
+{
+    let start = Instant::now();
+    let generated = generator(input);
+    let parse = start.elapsed();
+    let start = Instant::now();
+    let answer = generated.and_then(|generated| solver(generated.borrow()));
+    let solve = start.elapsed();
+    Outcome {
+        answer: answer.map(|answer| answer.to_string()),
+        parse,
+        solve,
+    }
+}
+
+/// For the days that solve the raw input.
+pub(crate) fn unparsed(input: &str) -> Result<&str, AocError> {
+    Ok(input)
+}
+
+/// Defines [`solutions`] from `(day, part, generator, solver)` tuples, which must be
+/// listed by day and part.
+macro_rules! solutions {
+    ($(($day:literal, $part:literal, $generator:path, $solver:path)),* $(,)?) => {
+        /// All solutions, ordered by day and part.
+        pub fn solutions() -> Vec<Solution> {
+            vec![$(Solution::new($day, $part, |input| timed(input, $generator, $solver))),*]
+        }
+    };
+}
+
+solutions! {
+    (1, 1, day01::input_generator, day01::part1),
+    (1, 2, day01::input_generator, day01::part2),
+    (2, 1, day02::input_generator, day02::count_safe),
+    (2, 2, day02::input_generator, day02::count_dampner_safe),
+    (3, 1, unparsed, day03::part1),
+    (3, 2, unparsed, day03::part2),
+    (4, 1, unparsed, day04::solve_part1),
+    (4, 2, unparsed, day04::solve_part2),
+    (5, 1, day05::parse_input, day05::part_1),
+    (5, 2, day05::parse_input, day05::part_2),
+    (6, 1, day06::parse, day06::part_1),
+    (6, 2, day06::parse, day06::part_2),
+    (7, 1, day07::input_generator, day07::part1),
+    (7, 2, day07::input_generator, day07::part2),
+    (8, 1, day08::input_generator, day08::part1),
+    (8, 2, day08::input_generator, day08::part2),
+    (9, 1, day09::parse_input, day09::part1),
+    (9, 2, day09::parse_input_p2, day09::part2),
+    (10, 1, day10::parse_input, day10::part1),
+    (10, 2, day10::parse_input, day10::part2),
+    (11, 1, day11::parse_input, day11::part1),
+    (11, 2, day11::parse_input, day11::part2),
+    (13, 1, day13::input_generator, day13::part1),
+    (13, 2, day13::input_generator_part2, day13::part2),
+    (14, 1, day14::parse_input, day14::part1),
+    (14, 2, day14::parse_input, day14::part2),
+}
+
+/// The solutions for `day`, only `part` of them if it is given.
+pub fn find(day: u32, part: Option<u32>) -> Vec<Solution> {
+    solutions()
+        .into_iter()
+        .filter(|s| s.day == day && part.is_none_or(|part| s.part == part))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solutions() {
+        let solutions = solutions();
+        assert!(solutions
+            .windows(2)
+            .all(|w| (w[0].day, w[0].part) < (w[1].day, w[1].part)));
+        assert!(solutions
+            .chunk_by(|a, b| a.day == b.day)
+            .all(|day| day.iter().map(|s| s.part).eq([1, 2])));
+        assert_eq!(find(7, None).len(), 2);
+        assert_eq!(find(12, None).len(), 0);
+
+        let [part2] = find(7, Some(2))[..] else {
+            panic!("day 7 has a part 2");
+        };
+        let input = "190: 10 19\n156: 15 6\n";
+        assert_eq!(part2.run(input).answer, Ok("346".to_string()));
+        assert!(part2.run("190:").answer.is_err());
+    }
+}
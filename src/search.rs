@@ -0,0 +1,643 @@
+//! Shortest path searches over a caller-defined graph.
+//!
+//! Nodes are usually grid positions, or a position paired with a [`Direction`] when
+//! turning has a cost, and the neighbour function is usually built from
+//! [`Grid::neighbours`].
+//!
+//! [`Direction`]: crate::grid::Direction
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet, VecDeque},
+    hash::Hash,
+};
+
+use itertools::Itertools;
+
+use crate::grid::{Direction, Grid};
+
+/// Distances and shortest-path predecessors found by [`bfs`], [`dijkstra`] or [`astar`].
+#[derive(Debug, Clone)]
+pub struct SearchResult<N> {
+    distances: HashMap<N, u64>,
+    predecessors: HashMap<N, Vec<N>>,
+    /// Nodes whose neighbours have been visited, they never become the predecessor of a
+    /// node settled before them so that zero-cost edges cannot close a cycle.
+    settled: HashSet<N>,
+    goal: Option<N>,
+}
+
+impl<N> SearchResult<N>
+where
+    N: Copy + Eq + Hash,
+{
+    fn new() -> Self {
+        Self {
+            distances: HashMap::new(),
+            predecessors: HashMap::new(),
+            settled: HashSet::new(),
+            goal: None,
+        }
+    }
+
+    /// Record `node` as reached from `from` at `distance`, returns `true` if this is a
+    /// new shortest distance and `node` has to be (re)visited.
+    fn relax(&mut self, from: N, node: N, distance: u64) -> bool {
+        match self.distances.get(&node) {
+            Some(&known) if known < distance => false,
+            Some(&known) if known == distance => {
+                if !self.settled.contains(&node) {
+                    self.predecessors.entry(node).or_default().push(from);
+                }
+                false
+            }
+            _ => {
+                self.distances.insert(node, distance);
+                self.predecessors.insert(node, vec![from]);
+                true
+            }
+        }
+    }
+
+    /// The first goal node reached, if any.
+    pub fn goal(&self) -> Option<N> {
+        self.goal
+    }
+
+    pub fn goal_distance(&self) -> Option<u64> {
+        self.distance(self.goal?)
+    }
+
+    pub fn distance(&self, node: N) -> Option<u64> {
+        self.distances.get(&node).copied()
+    }
+
+    /// Every visited node and its shortest distance from the nearest start.
+    pub fn distances(&self) -> &HashMap<N, u64> {
+        &self.distances
+    }
+
+    /// One shortest path from a start to `node`, both ends included.
+    pub fn path(&self, node: N) -> Option<Vec<N>> {
+        self.distances.get(&node)?;
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some(&previous) = self.predecessors.get(&current).and_then(|p| p.first()) {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+
+    /// Every shortest path from a start to `node`, both ends included.
+    ///
+    /// The number of paths can grow exponentially, use [`SearchResult::on_shortest_paths`]
+    /// when only the visited nodes matter.
+    pub fn all_paths(&self, node: N) -> Vec<Vec<N>> {
+        if !self.distances.contains_key(&node) {
+            return Vec::new();
+        }
+        let mut paths = Vec::new();
+        self.collect_paths(node, &mut vec![node], &mut paths);
+        paths
+    }
+
+    fn collect_paths(&self, node: N, suffix: &mut Vec<N>, paths: &mut Vec<Vec<N>>) {
+        match self.predecessors.get(&node) {
+            Some(previous) => previous.iter().for_each(|&p| {
+                suffix.push(p);
+                self.collect_paths(p, suffix, paths);
+                suffix.pop();
+            }),
+            None => paths.push(suffix.iter().rev().copied().collect()),
+        }
+    }
+
+    /// All nodes that lie on at least one shortest path to `node`.
+    pub fn on_shortest_paths(&self, node: N) -> HashSet<N> {
+        let mut seen = HashSet::new();
+        if !self.distances.contains_key(&node) {
+            return seen;
+        }
+        let mut stack = vec![node];
+        while let Some(current) = stack.pop() {
+            if seen.insert(current) {
+                stack.extend(self.predecessors.get(&current).into_iter().flatten());
+            }
+        }
+        seen
+    }
+}
+
+impl SearchResult<(usize, usize)> {
+    /// Distance to every position of a grid with the given `(width, height)`.
+    pub fn distance_grid(&self, dimensions: (usize, usize)) -> Grid<Option<u64>> {
+        let mut grid = Grid::new(dimensions.0, dimensions.1);
+        self.distances.iter().for_each(|(&pos, &distance)| {
+            if let Some(cell) = grid.get_mut(pos) {
+                *cell = Some(distance);
+            }
+        });
+        grid
+    }
+}
+
+/// Breadth-first search where every edge costs 1.
+///
+/// Stops at the first node where `goal` returns `true`, pass `|_| false` to get the
+/// distance to every reachable node.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(N) -> I,
+    mut goal: impl FnMut(N) -> bool,
+) -> SearchResult<N>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut result = SearchResult::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if result.distances.insert(start, 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        result.settled.insert(node);
+        if goal(node) {
+            result.goal = Some(node);
+            break;
+        }
+        let distance = result.distances[&node] + 1;
+        for next in neighbours(node) {
+            if result.relax(node, next, distance) {
+                queue.push_back(next);
+            }
+        }
+    }
+    result
+}
+
+/// Dijkstra's algorithm, `neighbours` returns each neighbour with the cost of the edge to it.
+pub fn dijkstra<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(N) -> I,
+    goal: impl FnMut(N) -> bool,
+) -> SearchResult<N>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    astar(starts, neighbours, |_| 0, goal)
+}
+
+/// A* search, `heuristic` must never overestimate the remaining cost to a goal for the
+/// result to hold shortest paths.
+pub fn astar<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(N) -> I,
+    mut heuristic: impl FnMut(N) -> u64,
+    mut goal: impl FnMut(N) -> bool,
+) -> SearchResult<N>
+where
+    N: Copy + Eq + Hash,
+    I: IntoIterator<Item = (N, u64)>,
+{
+    let mut result = SearchResult::new();
+    // The heap holds indices into `nodes` so that `N` does not need to be `Ord`.
+    let mut nodes = Vec::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        if result.distances.insert(start, 0).is_none() {
+            queue.push(Reverse((heuristic(start), 0, nodes.len())));
+            nodes.push(start);
+        }
+    }
+    while let Some(Reverse((_, distance, index))) = queue.pop() {
+        let node = nodes[index];
+        if result.distances[&node] < distance {
+            continue;
+        }
+        result.settled.insert(node);
+        if goal(node) {
+            result.goal = Some(node);
+            break;
+        }
+        for (next, cost) in neighbours(node) {
+            let next_distance = distance + cost;
+            if result.relax(node, next, next_distance) {
+                queue.push(Reverse((
+                    next_distance + heuristic(next),
+                    next_distance,
+                    nodes.len(),
+                )));
+                nodes.push(next);
+            }
+        }
+    }
+    result
+}
+
+impl<T> Grid<T> {
+    /// Breadth-first distance from the nearest of `sources` to every cell, moving between
+    /// cardinal neighbours where `passable` holds. Unreachable cells, and sources that
+    /// are out of bounds or impassable, are `None`.
+    pub fn distance_map(
+        &self,
+        sources: impl IntoIterator<Item = (usize, usize)>,
+        passable: impl Fn(&T) -> bool,
+    ) -> Grid<Option<u32>> {
+        let mut distances =
+            Grid::filled(self.width(), self.height(), None).with_topology(self.topology());
+        let mut queue = VecDeque::new();
+        for source in sources {
+            if self.get(source).is_some_and(&passable) && distances[source].is_none() {
+                distances[source] = Some(0);
+                queue.push_back(source);
+            }
+        }
+        while let Some(pos) = queue.pop_front() {
+            let distance = distances[pos].map(|d| d + 1);
+            for (next, cell) in self.neighbours(pos, Direction::cardinal()) {
+                if passable(cell) && distances[next].is_none() {
+                    distances[next] = distance;
+                    queue.push_back(next);
+                }
+            }
+        }
+        distances
+    }
+}
+
+/// A jump between two cells found by [`shortcuts`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    pub from: (usize, usize),
+    pub to: (usize, usize),
+    /// Steps saved compared to the shortest path without the jump.
+    pub saving: u32,
+}
+
+/// Every jump of at most `radius` Manhattan steps, from a cell reachable from the start
+/// to a cell that reaches the end, that saves at least `min_saving` steps.
+///
+/// `from_start` and `from_end` are [`Grid::distance_map`]s of the same grid from the
+/// start and the end, a jump costs its Manhattan length.
+///
+/// Panics if the two maps are not of the same size.
+pub fn shortcuts(
+    from_start: &Grid<Option<u32>>,
+    from_end: &Grid<Option<u32>>,
+    radius: usize,
+    min_saving: u32,
+) -> Vec<Shortcut> {
+    assert_eq!(
+        from_start.dimensions(),
+        from_end.dimensions(),
+        "distance maps of different grids"
+    );
+    let (width, height) = from_end.dimensions();
+    if width == 0 || height == 0 {
+        return Vec::new();
+    }
+    let Some(best) = from_start
+        .iter()
+        .filter_map(|(pos, &start)| Some(start? + from_end.get(pos).copied().flatten()?))
+        .min()
+    else {
+        return Vec::new();
+    };
+    let mut found = Vec::new();
+    for (from, start) in from_start.iter() {
+        let Some(start) = *start else { continue };
+        let (x, y) = from;
+        for to_y in y.saturating_sub(radius)..=y.saturating_add(radius).min(height - 1) {
+            let spare = radius - to_y.abs_diff(y);
+            for to_x in x.saturating_sub(spare)..=x.saturating_add(spare).min(width - 1) {
+                let Some(end) = from_end[(to_x, to_y)] else {
+                    continue;
+                };
+                let jump = (to_x.abs_diff(x) + to_y.abs_diff(y)) as u32;
+                let length = start + jump + end;
+                if length + min_saving <= best && length < best {
+                    found.push(Shortcut {
+                        from,
+                        to: (to_x, to_y),
+                        saving: best - length,
+                    });
+                }
+            }
+        }
+    }
+    found
+}
+
+/// Number of paths from every cell of a grid to a sink, found by [`Grid::count_paths`].
+#[derive(Clone)]
+pub struct PathCounts {
+    /// `None` where the count does not fit in a `u64`.
+    counts: Grid<Option<u64>>,
+    /// The first successor of each cell with a path to a sink.
+    next: Grid<Option<(usize, usize)>>,
+    reachable_sinks: Grid<usize>,
+}
+
+impl PathCounts {
+    /// Number of distinct paths from `pos` to any sink, `None` if there are more than a
+    /// `u64` can count.
+    pub fn paths_from(&self, pos: (usize, usize)) -> Option<u64> {
+        self.counts.get(pos).copied().unwrap_or(Some(0))
+    }
+
+    /// One path from `pos` to a sink, both included, `None` if there is none.
+    pub fn witness(&self, pos: (usize, usize)) -> Option<Vec<(usize, usize)>> {
+        if self.paths_from(pos) == Some(0) {
+            return None;
+        }
+        let mut path = vec![pos];
+        while let Some(next) = self.next[*path.last()?] {
+            path.push(next);
+        }
+        Some(path)
+    }
+
+    /// Number of distinct sinks reachable from `pos`.
+    pub fn sinks_from(&self, pos: (usize, usize)) -> usize {
+        self.reachable_sinks.get(pos).copied().unwrap_or(0)
+    }
+}
+
+impl<T> Grid<T> {
+    /// Count the paths from every cell to a cell where `sink` holds, moving in the
+    /// directions of `neighbourhood` from a cell to a neighbour when `edge(cell, neighbour)`
+    /// holds. Paths stop at the first sink they reach.
+    ///
+    /// Every cell is visited once, so the edges must not form a cycle, which is the case
+    /// when they always lead strictly uphill or downhill. Returns `None` if they do.
+    pub fn count_paths(
+        &self,
+        neighbourhood: &[Direction],
+        edge: impl Fn(&T, &T) -> bool,
+        sink: impl Fn(&T) -> bool,
+    ) -> Option<PathCounts> {
+        #[derive(Clone, Copy, PartialEq)]
+        enum State {
+            New,
+            Open,
+            Done,
+        }
+
+        let (width, height) = self.dimensions();
+        let mut state = Grid::filled(width, height, State::New);
+        let mut counts = Grid::filled(width, height, Some(0u64));
+        let mut next = Grid::filled(width, height, None);
+        let sinks = Grid::from_vec(width, height, self.iter().map(|(_, c)| sink(c)).collect())
+            .expect("one value per cell");
+        let successors = Grid::from_vec(
+            width,
+            height,
+            self.iter()
+                .map(|(pos, cell)| {
+                    self.neighbours(pos, neighbourhood.iter().copied())
+                        .filter(|(_, n)| !sinks[pos] && edge(cell, n))
+                        .map(|(n, _)| n)
+                        .collect_vec()
+                })
+                .collect(),
+        )
+        .expect("one value per cell");
+        // Bit sets of the sinks reachable from each cell, by index in `sink_index`.
+        let mut sink_index = Grid::filled(width, height, 0);
+        let mut sink_count = 0usize;
+        for (pos, _) in self.iter() {
+            if sinks[pos] {
+                sink_index[pos] = sink_count;
+                sink_count += 1;
+            }
+        }
+        let words = sink_count.div_ceil(64);
+        let mut reachable: Grid<Vec<u64>> = Grid::new(width, height);
+
+        for (start, _) in self.iter() {
+            if state[start] != State::New {
+                continue;
+            }
+            // Depth-first, each cell is pushed once to open it and is summed up once all
+            // of its successors are done.
+            let mut stack = vec![(start, false)];
+            while let Some((pos, expanded)) = stack.pop() {
+                if expanded {
+                    let mut bits = vec![0u64; words];
+                    if sinks[pos] {
+                        counts[pos] = Some(1);
+                        bits[sink_index[pos] / 64] |= 1 << (sink_index[pos] % 64);
+                    }
+                    for &succ in &successors[pos] {
+                        if counts[succ] != Some(0) && next[pos].is_none() {
+                            next[pos] = Some(succ);
+                        }
+                        counts[pos] = counts[pos]
+                            .zip(counts[succ])
+                            .and_then(|(count, more)| count.checked_add(more));
+                        bits.iter_mut()
+                            .zip(&reachable[succ])
+                            .for_each(|(bit, succ_bit)| *bit |= succ_bit);
+                    }
+                    reachable[pos] = bits;
+                    state[pos] = State::Done;
+                    continue;
+                }
+                if state[pos] != State::New {
+                    continue;
+                }
+                state[pos] = State::Open;
+                stack.push((pos, true));
+                for &succ in &successors[pos] {
+                    match state[succ] {
+                        State::New => stack.push((succ, false)),
+                        State::Open => return None,
+                        State::Done => {}
+                    }
+                }
+            }
+        }
+
+        let reachable_sinks = Grid::from_vec(
+            width,
+            height,
+            reachable
+                .iter()
+                .map(|(_, bits)| bits.iter().map(|b| b.count_ones() as usize).sum())
+                .collect(),
+        )
+        .expect("one value per cell");
+        Some(PathCounts {
+            counts,
+            next,
+            reachable_sinks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use indoc::indoc;
+
+    const MAZE: &str = indoc! {"
+        S...#
+        .##.#
+        ....E
+    "};
+
+    fn open_neighbours(grid: &Grid<char>, pos: (usize, usize)) -> Vec<(usize, usize)> {
+        grid.neighbours(pos, Direction::cardinal())
+            .filter(|(_, &c)| c != '#')
+            .map(|(p, _)| p)
+            .collect()
+    }
+
+    #[test]
+    fn test_bfs() {
+        let grid = Grid::parse_chars(MAZE).unwrap();
+        let result = bfs([(0, 0)], |p| open_neighbours(&grid, p), |p| grid[p] == 'E');
+        assert_eq!(result.goal(), Some((4, 2)));
+        assert_eq!(result.goal_distance(), Some(6));
+        let path = result.path((4, 2)).unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(result.all_paths((4, 2)).len(), 2);
+        assert_eq!(result.on_shortest_paths((4, 2)).len(), 11);
+    }
+
+    #[test]
+    fn test_multi_source_distance_grid() {
+        let grid = Grid::parse_chars(MAZE).unwrap();
+        let result = bfs([(0, 0), (4, 2)], |p| open_neighbours(&grid, p), |_| false);
+        let distances = result.distance_grid(grid.dimensions());
+        assert_eq!(distances[(0, 0)], Some(0));
+        assert_eq!(distances[(2, 2)], Some(2));
+        assert_eq!(distances[(3, 0)], Some(3));
+        assert_eq!(distances[(4, 0)], None);
+    }
+
+    #[test]
+    fn test_dijkstra_and_astar() {
+        let grid = Grid::parse("1163\n1381\n2136\n", |c| c.to_digit(10).ok_or(c)).unwrap();
+        let weighted = |p| {
+            grid.neighbours(p, Direction::cardinal())
+                .map(|(next, &cost)| (next, cost as u64))
+                .collect::<Vec<_>>()
+        };
+        let end = (3, 2);
+        let result = dijkstra([(0, 0)], weighted, |p| p == end);
+        assert_eq!(result.goal_distance(), Some(13));
+        let path = result.path(end).unwrap();
+        let cost: u64 = path[1..].iter().map(|&p| grid[p] as u64).sum();
+        assert_eq!(cost, 13);
+
+        let manhattan = |p: (usize, usize)| (p.0.abs_diff(end.0) + p.1.abs_diff(end.1)) as u64;
+        let result = astar([(0, 0)], weighted, manhattan, |p| p == end);
+        assert_eq!(result.goal_distance(), Some(13));
+    }
+
+    #[test]
+    fn test_zero_cost_cycle() {
+        let edges = |node: u8| match node {
+            0 => vec![(1, 0)],
+            1 => vec![(0, 0), (2, 1)],
+            _ => vec![],
+        };
+        let result = dijkstra([0], edges, |_| false);
+        assert_eq!(result.distance(2), Some(1));
+        assert_eq!(result.path(0), Some(vec![0]));
+        assert_eq!(result.path(2), Some(vec![0, 1, 2]));
+        assert_eq!(result.all_paths(2), [vec![0, 1, 2]]);
+        assert_eq!(result.on_shortest_paths(2).len(), 3);
+    }
+
+    #[test]
+    fn test_distance_map_and_shortcuts() {
+        let track = Grid::parse_chars(indoc! {"
+            #####
+            #S#E#
+            #.#.#
+            #...#
+            #####
+        "})
+        .unwrap();
+        let open = |&c: &char| c != '#';
+        let from_start = track.distance_map([(1, 1)], open);
+        let from_end = track.distance_map([(3, 1)], open);
+        assert_eq!(from_start[(3, 1)], Some(6));
+        assert_eq!(from_start[(0, 0)], None);
+        assert_eq!(from_end[(1, 1)], Some(6));
+        assert_eq!(track.distance_map([(0, 0)], open)[(1, 1)], None);
+
+        let found = shortcuts(&from_start, &from_end, 2, 2);
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&Shortcut {
+            from: (1, 1),
+            to: (3, 1),
+            saving: 4,
+        }));
+        assert!(found.contains(&Shortcut {
+            from: (1, 2),
+            to: (3, 2),
+            saving: 2,
+        }));
+        assert!(shortcuts(&from_start, &from_end, 2, 5).is_empty());
+        assert_eq!(shortcuts(&from_start, &from_end, 4, 1).len(), 4);
+
+        let empty = Grid::new(0, 0);
+        assert!(shortcuts(&empty, &empty, 2, 1).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "distance maps of different grids")]
+    fn test_shortcuts_of_different_grids() {
+        shortcuts(&Grid::new(2, 2), &Grid::new(3, 2), 2, 1);
+    }
+
+    #[test]
+    fn test_count_paths() {
+        let grid = Grid::parse("0123\n1234\n2345\n", |c| c.to_digit(10).ok_or(c)).unwrap();
+        let counts = grid
+            .count_paths(&Direction::cardinal(), |a, b| b == &(a + 1), |&c| c == 5)
+            .unwrap();
+        assert_eq!(counts.paths_from((0, 0)), Some(10));
+        assert_eq!(counts.paths_from((3, 2)), Some(1));
+        assert_eq!(counts.sinks_from((0, 0)), 1);
+        let witness = counts.witness((0, 0)).unwrap();
+        assert_eq!(witness.len(), 6);
+        assert_eq!(witness.last(), Some(&(3, 2)));
+
+        let fewer = grid
+            .count_paths(&Direction::cardinal(), |a, b| b == &(a + 1), |&c| c == 3)
+            .unwrap();
+        assert_eq!(fewer.paths_from((0, 0)), Some(7));
+        assert_eq!(fewer.sinks_from((0, 0)), 3);
+        assert_eq!(fewer.paths_from((3, 2)), Some(0));
+        assert_eq!(fewer.witness((3, 2)), None);
+
+        assert!(grid
+            .count_paths(&Direction::cardinal(), |a, b| a != b, |_| false)
+            .is_none());
+    }
+
+    #[test]
+    fn test_too_many_paths() {
+        // There are 70 choose 35 paths across, more than a `u64` holds.
+        let sink = (35, 35);
+        let mut grid = Grid::filled(36, 36, false);
+        grid[sink] = true;
+        let lattice = [Direction::East, Direction::South];
+        let counts = grid.count_paths(&lattice, |_, _| true, |&c| c).unwrap();
+        assert_eq!(counts.paths_from(sink), Some(1));
+        assert_eq!(counts.paths_from((35, 0)), Some(1));
+        assert_eq!(counts.paths_from((34, 34)), Some(2));
+        assert_eq!(counts.paths_from((0, 0)), None);
+        assert_eq!(counts.witness((0, 0)).unwrap().last(), Some(&sink));
+    }
+}